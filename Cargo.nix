@@ -7249,6 +7249,11 @@ rec {
             name = "serde_json";
             packageId = "serde_json";
           }
+          {
+            name = "serde_yaml";
+            packageId = "serde_yaml";
+            optional = true;
+          }
           {
             name = "snafu";
             packageId = "snafu 0.8.5";
@@ -7277,7 +7282,9 @@ rec {
             packageId = "serde_yaml";
           }
         ];
-
+        features = {
+          "test-util" = [ "dep:serde_yaml" ];
+        };
       };
       "stackable-hive-operator" = rec {
         crateName = "stackable-hive-operator";
@@ -7337,6 +7344,10 @@ rec {
             name = "serde_json";
             packageId = "serde_json";
           }
+          {
+            name = "serde_yaml";
+            packageId = "serde_yaml";
+          }
           {
             name = "snafu";
             packageId = "snafu 0.8.5";
@@ -7371,6 +7382,17 @@ rec {
             features = [ "chrono" "git2" ];
           }
         ];
+        devDependencies = [
+          {
+            name = "rstest";
+            packageId = "rstest";
+          }
+          {
+            name = "stackable-hive-crd";
+            packageId = "stackable-hive-crd";
+            features = [ "test-util" ];
+          }
+        ];
 
       };
       "stackable-operator" = rec {
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use stackable_operator::{
+    config::{fragment::Fragment, merge::Merge},
+    schemars::{self, JsonSchema},
+};
+
+#[derive(Clone, Debug, Default, Fragment, JsonSchema, PartialEq)]
+#[fragment_attrs(
+    derive(
+        Clone,
+        Debug,
+        Default,
+        Deserialize,
+        Merge,
+        JsonSchema,
+        PartialEq,
+        Serialize
+    ),
+    serde(rename_all = "camelCase")
+)]
+pub struct AutoscalingConfig {
+    /// The lower limit of replicas the HorizontalPodAutoscaler scales the role group down to.
+    /// Defaults to `1`.
+    pub min_replicas: Option<i32>,
+
+    /// The upper limit of replicas the HorizontalPodAutoscaler scales the role group up to.
+    /// If set, a HorizontalPodAutoscaler is created for the role group and takes over the
+    /// `replicas` of the StatefulSet.
+    pub max_replicas: Option<i32>,
+
+    /// The average CPU utilization of the Pods in percent of their CPU request, which the
+    /// HorizontalPodAutoscaler aims for.
+    pub target_cpu_utilization: Option<i32>,
+
+    /// The average memory utilization of the Pods in percent of their memory request, which the
+    /// HorizontalPodAutoscaler aims for.
+    pub target_memory_utilization: Option<i32>,
+}
+
+impl AutoscalingConfig {
+    pub const DEFAULT_MIN_REPLICAS: i32 = 1;
+
+    /// Whether a HorizontalPodAutoscaler is created for the role group
+    pub fn enabled(&self) -> bool {
+        self.max_replicas.is_some()
+    }
+
+    pub fn min_replicas(&self) -> i32 {
+        self.min_replicas.unwrap_or(Self::DEFAULT_MIN_REPLICAS)
+    }
+}
@@ -1,7 +1,14 @@
 use std::{collections::BTreeMap, str::FromStr};
 
+use autoscaling::{AutoscalingConfig, AutoscalingConfigFragment};
 use indoc::formatdoc;
-use security::AuthenticationConfig;
+use monitoring::MonitoringConfig;
+use network_policy::NetworkPolicyConfig;
+use s3::S3TuningConfig;
+use security::{
+    AuthenticationConfig, LdapAuthenticationConfig, SecurityContextConfig,
+    SecurityContextConfigFragment,
+};
 use serde::{Deserialize, Serialize};
 use snafu::{OptionExt, ResultExt, Snafu};
 use stackable_operator::{
@@ -17,7 +24,7 @@ use stackable_operator::{
     },
     config::{
         fragment::{self, Fragment, ValidationError},
-        merge::Merge,
+        merge::{Atomic, Merge},
     },
     k8s_openapi::apimachinery::pkg::api::resource::Quantity,
     kube::{runtime::reflector::ObjectRef, CustomResource, ResourceExt},
@@ -34,7 +41,13 @@ use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
 use crate::affinity::get_affinity;
 
 pub mod affinity;
+pub mod autoscaling;
+pub mod monitoring;
+pub mod network_policy;
+pub mod s3;
 pub mod security;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
 pub const APP_NAME: &str = "hive";
 
@@ -47,25 +60,39 @@ pub const STACKABLE_LOG_DIR: &str = "/stackable/log";
 pub const STACKABLE_LOG_DIR_NAME: &str = "log";
 pub const STACKABLE_LOG_CONFIG_MOUNT_DIR: &str = "/stackable/mount/log-config";
 pub const STACKABLE_LOG_CONFIG_MOUNT_DIR_NAME: &str = "log-config-mount";
+pub const TMP_DIR: &str = "/tmp";
+pub const TMP_DIR_NAME: &str = "tmp";
 
 // Config file names
 pub const CORE_SITE_XML: &str = "core-site.xml";
 pub const HIVE_SITE_XML: &str = "hive-site.xml";
+pub const HDFS_SITE_XML: &str = "hdfs-site.xml";
 pub const HIVE_ENV_SH: &str = "hive-env.sh";
 pub const HIVE_METASTORE_LOG4J2_PROPERTIES: &str = "metastore-log4j2.properties";
 pub const JVM_SECURITY_PROPERTIES_FILE: &str = "security.properties";
+pub const JVM_ARGS_FILE: &str = "jvm.args";
+pub const JMX_REMOTE_PASSWORD_FILE: &str = "jmxremote.password";
+pub const JMX_REMOTE_ACCESS_FILE: &str = "jmxremote.access";
 
 // Default ports
 pub const HIVE_PORT_NAME: &str = "hive";
 pub const HIVE_PORT: u16 = 9083;
 pub const METRICS_PORT_NAME: &str = "metrics";
 pub const METRICS_PORT: u16 = 9084;
+pub const JMX_REMOTE_PORT_NAME: &str = "jmx";
+pub const JMX_REMOTE_PORT: u16 = 9085;
 
 // Certificates and trust stores
 pub const SYSTEM_TRUST_STORE: &str = "/etc/pki/java/cacerts";
 pub const SYSTEM_TRUST_STORE_PASSWORD: &str = "changeit";
-pub const STACKABLE_TRUST_STORE: &str = "/stackable/truststore.p12";
+// Kept in the writable config dir, so that it can be built with a read-only root filesystem
+pub const STACKABLE_TRUST_STORE: &str = "/stackable/config/truststore.p12";
 pub const STACKABLE_TRUST_STORE_PASSWORD: &str = "changeit";
+pub const TRUST_STORE_PASSWORD_ENV: &str = "TRUST_STORE_PASSWORD";
+
+// JMX remote credentials
+pub const JMX_REMOTE_USERNAME_ENV: &str = "JMX_REMOTE_USERNAME";
+pub const JMX_REMOTE_PASSWORD_ENV: &str = "JMX_REMOTE_PASSWORD";
 pub const CERTS_DIR: &str = "/stackable/certificates/";
 
 // Metastore opts
@@ -80,6 +107,19 @@ pub const DB_USERNAME_PLACEHOLDER: &str = "xxx_db_username_xxx";
 pub const DB_PASSWORD_PLACEHOLDER: &str = "xxx_db_password_xxx";
 pub const DB_USERNAME_ENV: &str = "DB_USERNAME_ENV";
 pub const DB_PASSWORD_ENV: &str = "DB_PASSWORD_ENV";
+pub const DB_CREDENTIALS_DIR: &str = "/stackable/db-credentials";
+pub const DB_CREDENTIALS_DIR_NAME: &str = "db-credentials";
+
+// S3 proxy credentials
+pub const S3_PROXY_USERNAME_ENV: &str = "S3_PROXY_USERNAME";
+pub const S3_PROXY_PASSWORD_ENV: &str = "S3_PROXY_PASSWORD";
+
+// S3 endpoint from a Secret
+pub const S3_ENDPOINT_ENV: &str = "S3_ENDPOINT";
+pub const S3_REGION_ENV: &str = "S3_REGION";
+
+/// The number of replicas of role groups which do not specify `replicas`
+pub const DEFAULT_ROLEGROUP_REPLICAS: u16 = 1;
 
 const DEFAULT_METASTORE_GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_minutes_unchecked(5);
 
@@ -103,6 +143,26 @@ pub enum Error {
         role: String,
         roles: Vec<String>,
     },
+
+    #[snafu(display(
+        "the compactor initiator is enabled, but no compactor worker threads are configured"
+    ))]
+    CompactorWithoutWorkerThreads,
+
+    #[snafu(display(
+        "the managed and external warehouse directories must differ, but both are {dir:?}"
+    ))]
+    WarehouseDirsNotDistinct { dir: String },
+
+    #[snafu(display(
+        "the warehouse directory {dir:?} references the label {label:?}, which is not set on the HiveCluster"
+    ))]
+    MissingWarehouseDirLabel { dir: String, label: String },
+
+    #[snafu(display(
+        "the metastore Pods must not run as root, but the securityContext sets runAsUser to 0"
+    ))]
+    RunAsRoot,
 }
 
 /// A Hive cluster stacklet. This resource is managed by the Stackable operator for Apache Hive.
@@ -156,6 +216,10 @@ pub struct HiveClusterConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub s3: Option<S3ConnectionInlineOrReference>,
 
+    /// Additional settings for the S3A filesystem, e.g. retries or per-bucket overrides.
+    #[serde(default)]
+    pub s3_tuning: S3TuningConfig,
+
     /// Name of the Vector aggregator [discovery ConfigMap](DOCS_BASE_URL_PLACEHOLDER/concepts/service_discovery).
     /// It must contain the key `ADDRESS` with the address of the Vector aggregator.
     /// Follow the [logging tutorial](DOCS_BASE_URL_PLACEHOLDER/tutorials/logging-vector-aggregator)
@@ -179,6 +243,76 @@ pub struct HiveClusterConfig {
 
     /// Settings related to user [authentication](DOCS_BASE_URL_PLACEHOLDER/usage-guide/security).
     pub authentication: Option<AuthenticationConfig>,
+
+    /// The domain of the Kubernetes cluster, e.g. `cluster.local`, used to build the FQDNs of
+    /// the metastore Pods and Services, e.g. for discovery and the Kerberos principals.
+    /// Defaults to the cluster domain configured for or detected by the operator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster_domain: Option<String>,
+
+    /// Name of a Secret containing the password of the truststore used by Hive in the key `password`.
+    /// If not set, the default password `changeit` is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trust_store_password_secret: Option<String>,
+
+    /// Settings for the Services created by the operator.
+    #[serde(default)]
+    pub services: ServicesConfig,
+
+    /// Settings related to the [monitoring](DOCS_BASE_URL_PLACEHOLDER/hive/usage-guide/monitoring) of the metastore.
+    #[serde(default)]
+    pub monitoring: MonitoringConfig,
+
+    /// Settings for the `NetworkPolicy` restricting which clients can connect to the metastore.
+    #[serde(default)]
+    pub network_policy: NetworkPolicyConfig,
+
+    /// Additional files from a ConfigMap, which are added to the config directory of the
+    /// metastore, e.g. a custom policy file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub additional_config_files: Option<AdditionalConfigFiles>,
+
+    /// Registers the metastore instances in ZooKeeper, so that clients discover them via the
+    /// ZooKeeper quorum instead of a fixed list of metastore URIs. Only supported by Hive 4 and
+    /// newer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zookeeper_discovery: Option<ZookeeperDiscoveryConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServicesConfig {
+    /// Settings for the role Service, which is the main entry point for clients.
+    #[serde(default)]
+    pub role: ServiceConfig,
+
+    /// Settings for the headless role group Services.
+    #[serde(default)]
+    pub role_group: ServiceConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceConfig {
+    /// Whether the Service publishes the addresses of Pods that are not ready yet.
+    /// Defaults to `true` for the role group Services and is not set for the role Service.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_not_ready_addresses: Option<bool>,
+
+    /// With `ClientIP`, the connections of a client are routed to the same metastore Pod, which
+    /// keeps the clients sticky if multiple replicas back the Service. Not set by default, which
+    /// is equivalent to `None`. Ignored for the role group Services, as they are headless.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_affinity: Option<SessionAffinity>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+pub enum SessionAffinity {
+    #[serde(rename = "ClientIP")]
+    #[strum(serialize = "ClientIP")]
+    ClientIp,
+
+    None,
 }
 
 // TODO: Temporary solution until listener-operator is finished
@@ -214,6 +348,29 @@ pub struct HdfsConnection {
     pub config_map: String,
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdditionalConfigFiles {
+    /// Name of the ConfigMap containing the files.
+    pub config_map: String,
+
+    /// The keys of the ConfigMap which are added as files with the same name. They must not
+    /// clash with the files generated by the operator, e.g. `hive-site.xml`.
+    pub files: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZookeeperDiscoveryConfig {
+    /// The comma separated ZooKeeper servers, e.g. `zk-0.zk:2181,zk-1.zk:2181`.
+    pub quorum: String,
+
+    /// The ZooKeeper node the metastore instances are registered below, e.g. `hive-metastore`.
+    /// Defaults to the Hive default `hivemetastore`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
 #[derive(Display, EnumString, EnumIter)]
 #[strum(serialize_all = "camelCase")]
 pub enum HiveRole {
@@ -289,6 +446,218 @@ pub struct MetastoreStorageConfig {
     /// CRD version. The controller will warn if it's set to a non zero value.
     #[fragment_attrs(serde(default))]
     pub data: PvcConfig,
+
+    /// Additional PersistentVolumeClaims to create for every metastore Pod, keyed by volume name.
+    /// The volume names must not clash with the volumes created by the operator.
+    /// Please note that the volume claim templates of a StatefulSet can not be changed once it
+    /// has been created.
+    #[fragment_attrs(serde(default))]
+    pub additional_volumes: BTreeMap<String, AdditionalVolumeConfig>,
+
+    /// The `ephemeral-storage` request and limit of the Hive container, which is used for
+    /// e.g. scratch data and logs. If not set, Pods may be evicted when the node runs out of
+    /// ephemeral storage.
+    #[fragment_attrs(serde(default))]
+    pub ephemeral: EphemeralStorageConfig,
+
+    /// Whether the PersistentVolumeClaims of the `additionalVolumes` are deleted when the role
+    /// group is scaled down or deleted. By default they are retained.
+    #[fragment_attrs(serde(default))]
+    pub volume_claim_retention: VolumeClaimRetentionConfig,
+}
+
+#[derive(Clone, Debug, Default, Fragment, JsonSchema, PartialEq)]
+#[fragment_attrs(
+    derive(
+        Clone,
+        Debug,
+        Default,
+        Deserialize,
+        Merge,
+        JsonSchema,
+        PartialEq,
+        Serialize
+    ),
+    serde(rename_all = "camelCase")
+)]
+pub struct VolumeClaimRetentionConfig {
+    /// What happens to the PersistentVolumeClaims of Pods removed by scaling down.
+    pub when_scaled: Option<VolumeClaimRetentionPolicy>,
+
+    /// What happens to the PersistentVolumeClaims when the role group is deleted.
+    pub when_deleted: Option<VolumeClaimRetentionPolicy>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+pub enum VolumeClaimRetentionPolicy {
+    Retain,
+    Delete,
+}
+
+impl Atomic for VolumeClaimRetentionPolicy {}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum InitializeColumnInfo {
+    /// Load the information of all columns.
+    All,
+
+    /// Only load the information of the primary key columns.
+    Pk,
+
+    /// Do not load any column information.
+    None,
+}
+
+impl Atomic for InitializeColumnInfo {}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+pub enum PodManagementPolicy {
+    Parallel,
+    OrderedReady,
+}
+
+impl Atomic for PodManagementPolicy {}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+pub enum TerminationMessagePolicy {
+    /// The termination message is only read from the termination message file.
+    File,
+
+    /// The last lines of the container log are used if the termination message file is empty
+    /// and the container exited with an error.
+    FallbackToLogsOnError,
+}
+
+impl Atomic for TerminationMessagePolicy {}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+pub enum ReadinessCheck {
+    /// The metastore is ready once its Thrift port accepts connections.
+    ThriftPort,
+
+    /// The metastore is ready once `schematool -info` succeeds, i.e. the database is reachable and
+    /// contains the metastore schema.
+    SchemaTool,
+}
+
+impl Atomic for ReadinessCheck {}
+
+/// A product image replacing the image of the HiveCluster. It is replaced as a whole when merging
+/// the role and role group config.
+#[derive(Clone, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ProductImageOverride(pub ProductImage);
+
+impl Atomic for ProductImageOverride {}
+
+/// The strategy used to replace the Pods of a StatefulSet when its Pod template changes.
+// Not an internally tagged enum, as the Kubernetes CRD schema can not express variants with
+// different properties next to a shared `type` property.
+#[derive(Clone, Debug, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateStrategy {
+    /// Either `RollingUpdate` or `OnDelete`.
+    #[serde(rename = "type")]
+    pub strategy_type: UpdateStrategyType,
+
+    /// Only Pods with an ordinal greater than or equal to the partition are replaced, e.g. to
+    /// roll out a change to a single canary Pod first. Only used by the `RollingUpdate`
+    /// strategy. Defaults to `0`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition: Option<i32>,
+}
+
+impl Atomic for UpdateStrategy {}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+pub enum UpdateStrategyType {
+    /// The Pods are replaced one after another, starting with the highest ordinal.
+    RollingUpdate,
+
+    /// The Pods are only replaced once they are deleted manually.
+    OnDelete,
+}
+
+#[derive(Clone, Debug, Default, Fragment, JsonSchema, PartialEq)]
+#[fragment_attrs(
+    derive(
+        Clone,
+        Debug,
+        Default,
+        Deserialize,
+        Merge,
+        JsonSchema,
+        PartialEq,
+        Serialize
+    ),
+    serde(rename_all = "camelCase")
+)]
+pub struct EphemeralStorageConfig {
+    /// The amount of ephemeral storage requested, e.g. `1Gi`.
+    pub request: Option<Quantity>,
+
+    /// The maximum amount of ephemeral storage the container may use, e.g. `2Gi`.
+    pub limit: Option<Quantity>,
+}
+
+#[derive(Clone, Debug, Default, Fragment, JsonSchema, PartialEq)]
+#[fragment_attrs(
+    derive(
+        Clone,
+        Debug,
+        Default,
+        Deserialize,
+        Merge,
+        JsonSchema,
+        PartialEq,
+        Serialize
+    ),
+    serde(rename_all = "camelCase")
+)]
+pub struct LogVolumeConfig {
+    /// The maximum size of the metastore log files, including the rolled over ones, e.g. `100Mi`.
+    /// The size limit of the log volume is derived from it. Defaults to `10Mi`.
+    pub max_files_size: Option<Quantity>,
+
+    /// The storage medium backing the log volume, either `Disk` or `Memory`. A memory backed
+    /// volume avoids disk IO on high log throughput, but its size counts against the memory limit
+    /// of the container. Defaults to `Disk`.
+    pub medium: Option<LogVolumeMedium>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+pub enum LogVolumeMedium {
+    /// The default medium of the node, usually a disk.
+    Disk,
+
+    /// A tmpfs in the memory of the node.
+    Memory,
+}
+
+impl Atomic for LogVolumeMedium {}
+
+#[derive(Clone, Debug, Default, Fragment, JsonSchema, PartialEq)]
+#[fragment_attrs(
+    derive(
+        Clone,
+        Debug,
+        Default,
+        Deserialize,
+        Merge,
+        JsonSchema,
+        PartialEq,
+        Serialize
+    ),
+    serde(rename_all = "camelCase")
+)]
+pub struct AdditionalVolumeConfig {
+    /// The path in the Hive container the volume is mounted at.
+    pub mount_path: String,
+
+    #[fragment_attrs(serde(default, flatten))]
+    pub pvc: PvcConfig,
 }
 
 #[derive(Clone, Debug, Default, Fragment, JsonSchema, PartialEq)]
@@ -307,41 +676,566 @@ pub struct MetastoreStorageConfig {
 )]
 pub struct MetaStoreConfig {
     /// The location of default database for the Hive warehouse.
+    /// Environment variables of the metastore container can be referenced as `${env:NAME}`, e.g.
+    /// `s3a://bucket/${env:CLUSTER_ENV}/warehouse`, and are replaced when the container starts.
     /// Maps to the `hive.metastore.warehouse.dir` setting.
     pub warehouse_dir: Option<String>,
 
+    /// The location of the external tables of databases without an explicit location.
+    /// Maps to the `hive.metastore.warehouse.external.dir` setting.
+    pub warehouse_external_dir: Option<String>,
+
+    /// Template for both warehouse directories, in which `{type}` is replaced with `managed` or
+    /// `external`, e.g. `s3a://bucket/{type}/warehouse`. Hive places the databases below these
+    /// directories. An explicit `warehouseDir` or `warehouseExternalDir` takes precedence.
+    /// Labels of the HiveCluster can be referenced as `{label:<key>}` in all warehouse
+    /// directories, e.g. `s3a://bucket/{label:tenant}/{type}` for a prefix per tenant.
+    pub warehouse_dir_template: Option<String>,
+
+    /// Whether tables of tenants may be located in directories next to the warehouse directory.
+    /// Maps to the `hive.metastore.warehouse.tenant.colocation` setting.
+    pub warehouse_tenant_colocation: Option<bool>,
+
+    /// The directory for temporary files of queries, e.g. `/tmp/hive` or an HDFS path.
+    /// Maps to the `hive.exec.scratchdir` setting.
+    pub scratch_dir: Option<String>,
+
+    /// Whether lock files are created in the scratch directories of active sessions, so that
+    /// they are not removed by the cleanup. Maps to the `hive.scratchdir.lock` setting.
+    pub scratch_dir_lock: Option<bool>,
+
+    /// Whether the scratch directories of previous sessions are removed when the metastore starts.
+    /// Maps to the `hive.start.cleanup.scratchdir` setting.
+    pub cleanup_scratch_dir_on_start: Option<bool>,
+
+    /// The class used to store the delegation tokens, e.g.
+    /// `org.apache.hadoop.hive.thrift.DBTokenStore` or `org.apache.hadoop.hive.thrift.ZooKeeperTokenStore`.
+    /// A shared token store is required if multiple metastore replicas issue delegation tokens.
+    /// Maps to the `hive.cluster.delegation.token.store.class` setting.
+    pub delegation_token_store_class: Option<String>,
+
+    /// The ZooKeeper connect string used by the `ZooKeeperTokenStore`, e.g. `zk-1:2181,zk-2:2181/hive`.
+    /// Maps to the `hive.cluster.delegation.token.store.zookeeper.connectString` setting.
+    pub delegation_token_store_zookeeper_connect_string: Option<String>,
+
+    /// Whether Hive supports concurrency, which is required for transactional (ACID) tables.
+    /// Maps to the `hive.support.concurrency` setting.
+    pub support_concurrency: Option<bool>,
+
+    /// The transaction manager, e.g. `org.apache.hadoop.hive.ql.lockmgr.DbTxnManager`.
+    /// Defaults to the `DbTxnManager` if concurrency is enabled.
+    /// Maps to the `hive.txn.manager` setting.
+    pub txn_manager: Option<String>,
+
+    /// Listeners which are called within the transaction of a metastore operation, e.g. for
+    /// replication. If concurrency is enabled, the listeners are appended to the
+    /// `DbNotificationListener`, which ACID tables rely on for their notifications.
+    /// Maps to the comma separated `hive.metastore.transactional.event.listeners` setting.
+    pub transactional_event_listeners: Option<CommaSeparatedList>,
+
+    /// Whether the compactor initiator, which schedules the compactions of transactional tables,
+    /// runs on this metastore. Maps to the `hive.compactor.initiator.on` setting.
+    pub compactor_initiator_on: Option<bool>,
+
+    /// The number of compactor worker threads running on this metastore. Needs to be greater
+    /// than zero if the compactor initiator is enabled.
+    /// Maps to the `hive.compactor.worker.threads` setting.
+    pub compactor_worker_threads: Option<u16>,
+
+    /// Whether the metastore fires events for DML operations, e.g. for change data capture.
+    /// Maps to the `hive.metastore.dml.events` setting.
+    pub dml_events: Option<bool>,
+
+    /// Whether the notification events contain the complete Thrift objects, e.g. the tables and
+    /// partitions, which change data capture pipelines need to replicate the metadata.
+    /// Maps to the `hive.metastore.notifications.add.thrift.objects` setting.
+    pub notifications_add_thrift_objects: Option<bool>,
+
+    /// The class encoding the notification events, e.g.
+    /// `org.apache.hadoop.hive.metastore.messaging.json.JSONMessageEncoder` for change data
+    /// capture consumers requiring plain JSON.
+    /// The operator sets no default and keeps the encoder of the Hive version, i.e. the
+    /// `JSONMessageEncoder` of Hive 3 and the `GzipJSONMessageEncoder` of Hive 4, as changing it
+    /// would break existing consumers. Maps to the `hive.metastore.event.message.factory` setting.
+    pub event_message_factory: Option<String>,
+
+    /// If set, the metastore waits up to this time period for the database to be reachable
+    /// before it is started, e.g. `2m`. Otherwise the metastore crash-loops until the database
+    /// is available.
+    #[fragment_attrs(serde(default))]
+    pub wait_for_database_timeout: Option<Duration>,
+
+    /// How often a metastore request is retried on transient database errors.
+    /// Maps to the `hive.metastore.hmshandler.retry.attempts` setting.
+    pub handler_retry_attempts: Option<u32>,
+
+    /// The time period between the retries of a metastore request, e.g. `2s`.
+    /// Maps to the `hive.metastore.hmshandler.retry.interval` setting.
+    #[fragment_attrs(serde(default))]
+    pub handler_retry_interval: Option<Duration>,
+
+    /// How often a metastore client retries a failed call, e.g. the clients embedded in the
+    /// metastore. Maps to the `hive.metastore.failure.retries` setting.
+    pub client_failure_retries: Option<u32>,
+
+    /// The time period a metastore client waits before reconnecting, e.g. `5s`. A longer delay
+    /// avoids many clients reconnecting at once after a restart.
+    /// Maps to the `hive.metastore.client.connect.retry.delay` setting.
+    #[fragment_attrs(serde(default))]
+    pub client_connect_retry_delay: Option<Duration>,
+
+    /// The time period after which a metastore client closes its connection and reconnects, e.g.
+    /// `10m`, which spreads long-lived clients across the metastore replicas.
+    /// Maps to the `hive.metastore.client.socket.lifetime` setting.
+    #[fragment_attrs(serde(default))]
+    pub client_socket_lifetime: Option<Duration>,
+
+    /// Which column information DataNucleus loads from the database when the metastore starts.
+    /// `NONE` speeds up the start on databases with slow metadata queries.
+    /// Maps to the `datanucleus.rdbms.initializeColumnInfo` setting.
+    pub initialize_column_info: Option<InitializeColumnInfo>,
+
+    /// Classes which are run when the metastore is initialized, e.g. to register metrics or tracing.
+    /// Maps to the comma separated `hive.metastore.init.hooks` setting.
+    pub init_hooks: Option<CommaSeparatedList>,
+
+    /// Listeners which are called at the end of every metastore function, e.g. for metrics.
+    /// Maps to the comma separated `hive.metastore.end.function.listeners` setting.
+    pub end_function_listeners: Option<CommaSeparatedList>,
+
+    /// The class filtering the metadata returned to clients, e.g. for authorization-aware filtering.
+    /// Maps to the `hive.metastore.filter.hook` setting.
+    pub filter_hook: Option<String>,
+
+    /// A regular expression all partition names have to match, e.g. `[\w\-=]*`, to reject
+    /// malformed partition names. Maps to the `hive.metastore.partition.name.whitelist.pattern`
+    /// setting.
+    pub partition_name_whitelist_pattern: Option<String>,
+
+    /// The maximum number of partitions a single request may fetch, e.g. to protect the metastore
+    /// from queries scanning all partitions of a huge table. Requests exceeding the limit fail,
+    /// Hive has no setting to truncate them instead. Defaults to no limit.
+    /// Maps to the `hive.metastore.limit.partition.request` setting.
+    pub limit_partition_request: Option<u32>,
+
+    /// The class storing the metadata, e.g. `org.apache.hadoop.hive.metastore.cache.CachedStore`
+    /// to cache the metadata in memory. Maps to the `hive.metastore.rawstore.impl` setting.
+    pub raw_store_impl: Option<String>,
+
+    /// The class the `CachedStore` delegates to, defaults to the `ObjectStore` of Hive.
+    /// Maps to the `hive.metastore.cached.rawstore.impl` setting.
+    pub cached_raw_store_impl: Option<String>,
+
+    /// How often the `CachedStore` refreshes its cache from the database, e.g. `60s`.
+    /// Maps to the `hive.metastore.cached.rawstore.cache.update.frequency` setting.
+    #[fragment_attrs(serde(default))]
+    pub cached_raw_store_cache_update_frequency: Option<Duration>,
+
+    /// The maximum memory the `CachedStore` uses for its cache, e.g. `1Gb`.
+    /// Maps to the `hive.metastore.cached.rawstore.max.cache.memory` setting.
+    pub cached_raw_store_max_cache_memory: Option<String>,
+
+    /// Whether the metastore Thrift interface uses SASL. Defaults to `true` if Kerberos is enabled.
+    /// Maps to the `hive.metastore.sasl.enabled` setting.
+    pub sasl_enabled: Option<bool>,
+
+    /// The address the metastore Thrift interface binds to, e.g. `0.0.0.0` or the address of a
+    /// single network interface of multi-homed Pods. Binds to all interfaces if not set.
+    /// Maps to the `hive.metastore.thrift.bind.host` setting.
+    pub thrift_bind_host: Option<String>,
+
+    /// Whether TCP keepalive is enabled for the client connections of the metastore, so that idle
+    /// connections are not dropped by firewalls. Maps to the `hive.metastore.server.tcp.keepalive`
+    /// setting.
+    pub tcp_keepalive: Option<bool>,
+
+    /// Whether the Thrift interface uses the framed transport, which some clients require.
+    /// All clients have to use the framed transport if it is enabled.
+    /// Maps to the `hive.metastore.thrift.framed.transport.enabled` setting.
+    pub thrift_framed_transport_enabled: Option<bool>,
+
+    /// The umask used when creating files and directories, e.g. `022` or `077`.
+    /// Maps to the `fs.permissions.umask-mode` setting in the `core-site.xml`.
+    pub umask_mode: Option<String>,
+
+    /// Whether the metastore creates the directories of databases, tables and partitions as the
+    /// user of the client instead of its own user, so that they are owned by the client user.
+    /// Maps to the `hive.metastore.execute.setugi` setting.
+    pub execute_setugi: Option<bool>,
+
+    /// Whether the metastore checks the permissions of the client user on the directory of a
+    /// database, table or partition before dropping it.
+    /// Maps to the `hive.metastore.authorization.storage.checks` setting.
+    pub authorization_storage_checks: Option<bool>,
+
+    /// A command put in front of the metastore process, e.g. to run it with a profiler or
+    /// `numactl`. The operator-generated start command is kept and passed as arguments to it.
+    pub command_wrapper: Option<String>,
+
+    /// The working directory of the metastore container, e.g. for JDBC drivers or SerDes which
+    /// resolve relative paths. Defaults to the working directory of the image.
+    pub working_dir: Option<String>,
+
+    /// A shell command run in the metastore container before it is sent SIGTERM, e.g.
+    /// `sleep 15` to let clients drain while the Pod is removed from the Service endpoints.
+    /// It counts towards the `gracefulShutdownTimeout`.
+    pub pre_stop_command: Option<String>,
+
+    /// Whether the effective JVM arguments of the metastore are written to the `jvm.args` key of
+    /// the role group ConfigMap, e.g. to debug heap issues without exec-ing into the Pods.
+    /// Defaults to `false`.
+    pub render_jvm_args: Option<bool>,
+
+    /// Whether the JMX Prometheus exporter is attached to the metastore as a Java agent.
+    /// Defaults to `true`. Can be disabled in favour of the native Hive metrics reporters.
+    pub jmx_exporter_enabled: Option<bool>,
+
+    /// Whether the metastore collects its metrics, written as `hive.metastore.metrics.enabled`.
+    /// Defaults to `true`. A role group setting takes precedence over the role, e.g. to only
+    /// collect metrics on a canary role group.
+    pub metrics_enabled: Option<bool>,
+
+    /// Name of a Secret containing the keys `username` and `password`. If set, the JMX remote port
+    /// 9085 is opened with password authentication, e.g. to take thread dumps of a hung metastore.
+    pub jmx_remote_credentials_secret: Option<String>,
+
+    /// The native Hive metrics reporters, any of `JSON_FILE`, `JMX`, `CONSOLE` and `HADOOP2`.
+    /// Maps to the comma separated `metastore.metrics.reporters` and the legacy
+    /// `hive.service.metrics.reporter` setting.
+    pub metrics_reporters: Option<CommaSeparatedList<MetricsReporter>>,
+
+    /// The file the `JSON_FILE` metrics reporter writes to, e.g. to be picked up by a log or
+    /// metrics collector. Maps to the `hive.service.metrics.file.location` setting.
+    pub metrics_file_location: Option<String>,
+
+    /// How often the `JSON_FILE` metrics reporter writes the metrics, e.g. `30s`.
+    /// Maps to the `hive.service.metrics.file.frequency` setting.
+    #[fragment_attrs(serde(default))]
+    pub metrics_file_frequency: Option<Duration>,
+
     #[fragment_attrs(serde(default))]
     pub resources: Resources<MetastoreStorageConfig, NoRuntimeLimits>,
 
+    /// The memory request of the metastore container, e.g. `512Mi`, which may be lower than the
+    /// memory limit to allow bursting. Defaults to the memory limit. The Java heap is still derived
+    /// from the memory limit.
+    pub memory_request: Option<Quantity>,
+
     #[fragment_attrs(serde(default))]
     pub logging: Logging<Container>,
 
+    /// Settings for the volume the metastore writes its log files to.
+    #[fragment_attrs(serde(default))]
+    pub log_volume: LogVolumeConfig,
+
     #[fragment_attrs(serde(default))]
     pub affinity: StackableAffinity,
 
-    /// Time period Pods have to gracefully shut down, e.g. `30m`, `1h` or `2d`. Consult the operator documentation for details.
+    /// Annotations added to the Pods of the StatefulSet, e.g. `sidecar.istio.io/inject: "true"`.
+    /// In contrast to annotations set on the HiveCluster, these are only added to the Pod template.
+    #[fragment_attrs(serde(default))]
+    pub pod_annotations: BTreeMap<String, String>,
+
+    /// Settings for the security context of the metastore Pods.
+    #[fragment_attrs(serde(default))]
+    pub security_context: SecurityContextConfig,
+
+    /// Whether the token of the ServiceAccount is mounted into the metastore Pods. The metastore
+    /// does not call the Kubernetes API, so it can be disabled to comply with security policies.
+    /// Defaults to `true`.
+    pub automount_service_account_token: Option<bool>,
+
+    /// Minimum number of seconds a newly created Pod has to be ready before it is considered
+    /// available, which slows down rolling updates of the StatefulSet. Defaults to `0`.
+    pub min_ready_seconds: Option<i32>,
+
+    /// The strategy used to update the Pods of the StatefulSet, either `RollingUpdate` with an
+    /// optional `partition` or `OnDelete`. Defaults to a `RollingUpdate` of all Pods.
+    pub update_strategy: Option<UpdateStrategy>,
+
+    /// Whether the Pods of the StatefulSet are started and stopped in `Parallel` or one after
+    /// another (`OrderedReady`), e.g. for ordered startup dependencies. Defaults to `Parallel`.
+    pub pod_management_policy: Option<PodManagementPolicy>,
+
+    /// How the termination message of the metastore container is populated, either `File` or
+    /// `FallbackToLogsOnError`. The latter shows the last log lines in the Pod status after a
+    /// crash. Defaults to `FallbackToLogsOnError`.
+    pub termination_message_policy: Option<TerminationMessagePolicy>,
+
+    /// The file the termination message of the metastore container is read from. Defaults to
+    /// `/dev/termination-log`.
+    pub termination_message_path: Option<String>,
+
+    /// How the readiness probe of the metastore container checks the metastore, either
+    /// `ThriftPort` or `SchemaTool`. The latter also verifies the connection to the database and
+    /// the presence of the schema, but starts a JVM on every check. Defaults to `ThriftPort`.
+    pub readiness_check: Option<ReadinessCheck>,
+
+    /// The product image of the role group, which replaces the image of the HiveCluster as a
+    /// whole, e.g. to run a newer version on a canary role group.
+    pub image: Option<ProductImageOverride>,
+
+    /// Settings for the HorizontalPodAutoscaler of the role group. Only supported with an external
+    /// database, as the embedded Derby database can not be shared between replicas.
     #[fragment_attrs(serde(default))]
+    pub autoscaling: AutoscalingConfig,
+
+    /// Time period Pods have to gracefully shut down, e.g. `30m`, `1h` or `2d`. Consult the operator documentation for details.
+    #[fragment_attrs(serde(default, deserialize_with = "deserialize_graceful_shutdown_timeout"))]
     pub graceful_shutdown_timeout: Option<Duration>,
 }
 
+/// Parses the `gracefulShutdownTimeout` with an error naming the field and the expected format,
+/// instead of the generic error of [`Duration`].
+fn deserialize_graceful_shutdown_timeout<'de, D>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|timeout| {
+            Duration::from_str(&timeout).map_err(|err| {
+                serde::de::Error::custom(format!(
+                    "invalid gracefulShutdownTimeout {timeout:?}, expected a duration like \"30s\", \"5m\" or \"1h30m\": {err}"
+                ))
+            })
+        })
+        .transpose()
+}
+
 impl MetaStoreConfig {
     // metastore
     pub const CONNECTION_URL: &'static str = "javax.jdo.option.ConnectionURL";
     pub const CONNECTION_DRIVER_NAME: &'static str = "javax.jdo.option.ConnectionDriverName";
     pub const CONNECTION_USER_NAME: &'static str = "javax.jdo.option.ConnectionUserName";
     pub const CONNECTION_PASSWORD: &'static str = "javax.jdo.option.ConnectionPassword";
+    pub const DATANUCLEUS_INITIALIZE_COLUMN_INFO: &'static str =
+        "datanucleus.rdbms.initializeColumnInfo";
+    pub const HMS_HANDLER_RETRY_ATTEMPTS: &'static str = "hive.metastore.hmshandler.retry.attempts";
+    pub const HMS_HANDLER_RETRY_INTERVAL: &'static str = "hive.metastore.hmshandler.retry.interval";
+    pub const CLIENT_FAILURE_RETRIES: &'static str = "hive.metastore.failure.retries";
+    pub const CLIENT_CONNECT_RETRY_DELAY: &'static str =
+        "hive.metastore.client.connect.retry.delay";
+    pub const CLIENT_SOCKET_LIFETIME: &'static str = "hive.metastore.client.socket.lifetime";
     pub const METASTORE_METRICS_ENABLED: &'static str = "hive.metastore.metrics.enabled";
     pub const METASTORE_WAREHOUSE_DIR: &'static str = "hive.metastore.warehouse.dir";
+    pub const METASTORE_WAREHOUSE_EXTERNAL_DIR: &'static str =
+        "hive.metastore.warehouse.external.dir";
+    pub const METASTORE_WAREHOUSE_TENANT_COLOCATION: &'static str =
+        "hive.metastore.warehouse.tenant.colocation";
+    pub const SCRATCH_DIR: &'static str = "hive.exec.scratchdir";
+    pub const SCRATCH_DIR_LOCK: &'static str = "hive.scratchdir.lock";
+    pub const START_CLEANUP_SCRATCH_DIR: &'static str = "hive.start.cleanup.scratchdir";
+    pub const DELEGATION_TOKEN_STORE_CLASS: &'static str =
+        "hive.cluster.delegation.token.store.class";
+    pub const DELEGATION_TOKEN_STORE_ZOOKEEPER_CONNECT_STRING: &'static str =
+        "hive.cluster.delegation.token.store.zookeeper.connectString";
+    pub const SUPPORT_CONCURRENCY: &'static str = "hive.support.concurrency";
+    pub const TXN_MANAGER: &'static str = "hive.txn.manager";
+    pub const DB_TXN_MANAGER: &'static str = "org.apache.hadoop.hive.ql.lockmgr.DbTxnManager";
+    pub const TRANSACTIONAL_EVENT_LISTENERS: &'static str =
+        "hive.metastore.transactional.event.listeners";
+    pub const DB_NOTIFICATION_LISTENER: &'static str =
+        "org.apache.hive.hcatalog.listener.DbNotificationListener";
+    pub const COMPACTOR_INITIATOR_ON: &'static str = "hive.compactor.initiator.on";
+    pub const COMPACTOR_WORKER_THREADS: &'static str = "hive.compactor.worker.threads";
+    pub const METASTORE_DML_EVENTS: &'static str = "hive.metastore.dml.events";
+    pub const METASTORE_NOTIFICATIONS_ADD_THRIFT_OBJECTS: &'static str =
+        "hive.metastore.notifications.add.thrift.objects";
+    pub const METASTORE_EVENT_MESSAGE_FACTORY: &'static str =
+        "hive.metastore.event.message.factory";
+    /// The [`Self::METASTORE_EVENT_MESSAGE_FACTORY`] writing plain JSON notification events
+    pub const METASTORE_EVENT_MESSAGE_FACTORY_JSON: &'static str =
+        "org.apache.hadoop.hive.metastore.messaging.json.JSONMessageEncoder";
+    pub const METRICS_REPORTER: &'static str = "hive.service.metrics.reporter";
+    pub const METASTORE_METRICS_REPORTERS: &'static str = "metastore.metrics.reporters";
+    pub const METRICS_FILE_LOCATION: &'static str = "hive.service.metrics.file.location";
+    pub const METRICS_FILE_FREQUENCY: &'static str = "hive.service.metrics.file.frequency";
+    pub const METASTORE_SASL_ENABLED: &'static str = "hive.metastore.sasl.enabled";
+    pub const METASTORE_FILTER_HOOK: &'static str = "hive.metastore.filter.hook";
+    pub const METASTORE_PARTITION_NAME_WHITELIST_PATTERN: &'static str =
+        "hive.metastore.partition.name.whitelist.pattern";
+    pub const METASTORE_LIMIT_PARTITION_REQUEST: &'static str =
+        "hive.metastore.limit.partition.request";
+    pub const METASTORE_RAW_STORE_IMPL: &'static str = "hive.metastore.rawstore.impl";
+    pub const METASTORE_CACHED_RAW_STORE_IMPL: &'static str = "hive.metastore.cached.rawstore.impl";
+    pub const METASTORE_CACHED_RAW_STORE_CACHE_UPDATE_FREQUENCY: &'static str =
+        "hive.metastore.cached.rawstore.cache.update.frequency";
+    pub const METASTORE_CACHED_RAW_STORE_MAX_CACHE_MEMORY: &'static str =
+        "hive.metastore.cached.rawstore.max.cache.memory";
+    pub const METASTORE_THRIFT_BIND_HOST: &'static str = "hive.metastore.thrift.bind.host";
+    pub const METASTORE_SERVER_TCP_KEEPALIVE: &'static str = "hive.metastore.server.tcp.keepalive";
+    pub const METASTORE_THRIFT_FRAMED_TRANSPORT_ENABLED: &'static str =
+        "hive.metastore.thrift.framed.transport.enabled";
+    pub const METASTORE_INIT_HOOKS: &'static str = "hive.metastore.init.hooks";
+    pub const METASTORE_SERVICE_DISCOVERY_MODE: &'static str =
+        "hive.metastore.service.discovery.mode";
+    pub const METASTORE_URIS: &'static str = "hive.metastore.uris";
+    pub const METASTORE_ZOOKEEPER_NAMESPACE: &'static str = "hive.metastore.zookeeper.namespace";
+    pub const METASTORE_END_FUNCTION_LISTENERS: &'static str =
+        "hive.metastore.end.function.listeners";
+    pub const METASTORE_EXECUTE_SETUGI: &'static str = "hive.metastore.execute.setugi";
+    pub const METASTORE_AUTHORIZATION_STORAGE_CHECKS: &'static str =
+        "hive.metastore.authorization.storage.checks";
+    // core-site
+    pub const FS_PERMISSIONS_UMASK_MODE: &'static str = "fs.permissions.umask-mode";
     // S3
     pub const S3_ENDPOINT: &'static str = "fs.s3a.endpoint";
+    pub const S3_ENDPOINT_REGION: &'static str = "fs.s3a.endpoint.region";
     pub const S3_ACCESS_KEY: &'static str = "fs.s3a.access.key";
     pub const S3_SECRET_KEY: &'static str = "fs.s3a.secret.key";
     pub const S3_SSL_ENABLED: &'static str = "fs.s3a.connection.ssl.enabled";
     pub const S3_PATH_STYLE_ACCESS: &'static str = "fs.s3a.path.style.access";
+    pub const S3_ATTEMPTS_MAXIMUM: &'static str = "fs.s3a.attempts.maximum";
+    pub const S3_RETRY_LIMIT: &'static str = "fs.s3a.retry.limit";
+    pub const S3_CHANGE_DETECTION_MODE: &'static str = "fs.s3a.change.detection.mode";
+    pub const S3_CHANGE_DETECTION_SOURCE: &'static str = "fs.s3a.change.detection.source";
+    pub const S3_AWS_CREDENTIALS_PROVIDER: &'static str = "fs.s3a.aws.credentials.provider";
+    pub const S3_CANNED_ACL: &'static str = "fs.s3a.canned.acl";
+    pub const S3_SERVER_SIDE_ENCRYPTION_ALGORITHM: &'static str =
+        "fs.s3a.server-side-encryption-algorithm";
+    pub const S3_SERVER_SIDE_ENCRYPTION_KEY: &'static str = "fs.s3a.server-side-encryption.key";
+    pub const S3_PROXY_HOST: &'static str = "fs.s3a.proxy.host";
+    pub const S3_PROXY_PORT: &'static str = "fs.s3a.proxy.port";
+    pub const S3_PROXY_USERNAME: &'static str = "fs.s3a.proxy.username";
+    pub const S3_PROXY_PASSWORD: &'static str = "fs.s3a.proxy.password";
+    pub const S3_BUCKET_PREFIX: &'static str = "fs.s3a.bucket.";
+
+    /// The managed and external warehouse directories written to the `hive-site.xml`
+    pub fn warehouse_dirs(&self) -> (Option<String>, Option<String>) {
+        warehouse_dirs(
+            self.warehouse_dir.as_deref(),
+            self.warehouse_external_dir.as_deref(),
+            self.warehouse_dir_template.as_deref(),
+        )
+    }
+
+    /// The `hive-site.xml` properties which are derived from several settings, e.g. the transaction
+    /// manager from `supportConcurrency` and `txnManager`. As the settings may be spread over the
+    /// role and the role group, they are derived from the merged config.
+    pub fn derived_hive_site_properties(&self, hive: &HiveCluster) -> BTreeMap<String, String> {
+        let mut result = BTreeMap::new();
+
+        let (warehouse_dir, warehouse_external_dir) = self.warehouse_dirs();
+        for (property_name, dir) in [
+            (MetaStoreConfig::METASTORE_WAREHOUSE_DIR, warehouse_dir),
+            (
+                MetaStoreConfig::METASTORE_WAREHOUSE_EXTERNAL_DIR,
+                warehouse_external_dir,
+            ),
+        ] {
+            if let Some(dir) = dir {
+                // Missing labels are rejected when the config is merged
+                let dir = substitute_labels(&dir, hive.labels()).unwrap_or(dir);
+                result.insert(property_name.to_string(), dir);
+            }
+        }
+
+        // ACID tables need a transaction manager which is able to handle the locks
+        let txn_manager = match (&self.txn_manager, self.support_concurrency) {
+            (Some(txn_manager), _) => Some(txn_manager.to_string()),
+            (None, Some(true)) => Some(MetaStoreConfig::DB_TXN_MANAGER.to_string()),
+            (None, _) => None,
+        };
+        if let Some(txn_manager) = txn_manager {
+            result.insert(MetaStoreConfig::TXN_MANAGER.to_string(), txn_manager);
+        }
+
+        let mut transactional_event_listeners = Vec::new();
+        if self.support_concurrency == Some(true) {
+            transactional_event_listeners
+                .push(MetaStoreConfig::DB_NOTIFICATION_LISTENER.to_string());
+        }
+        for listener in self.transactional_event_listeners.iter().flat_map(|l| &l.0) {
+            if !transactional_event_listeners.contains(listener) {
+                transactional_event_listeners.push(listener.clone());
+            }
+        }
+        if !transactional_event_listeners.is_empty() {
+            result.insert(
+                MetaStoreConfig::TRANSACTIONAL_EVENT_LISTENERS.to_string(),
+                CommaSeparatedList(transactional_event_listeners).to_string(),
+            );
+        }
+
+        result
+    }
+
+    /// Checks the merged config for combinations of settings Hive can not work with
+    fn validate(&self) -> Result<(), Error> {
+        if self.compactor_initiator_on == Some(true)
+            && self.compactor_worker_threads.unwrap_or_default() == 0
+        {
+            return CompactorWithoutWorkerThreadsSnafu.fail();
+        }
+
+        if let (Some(managed), Some(external)) = self.warehouse_dirs() {
+            if managed == external {
+                return WarehouseDirsNotDistinctSnafu { dir: managed }.fail();
+            }
+        }
+
+        // The Pods are marked with runAsNonRoot, so Kubernetes would refuse to start them
+        if self.security_context.run_as_user == Some(0) {
+            return RunAsRootSnafu.fail();
+        }
+
+        Ok(())
+    }
 
     fn default_config(cluster_name: &str, role: &HiveRole) -> MetaStoreConfigFragment {
         MetaStoreConfigFragment {
             warehouse_dir: None,
+            warehouse_external_dir: None,
+            warehouse_dir_template: None,
+            warehouse_tenant_colocation: None,
+            scratch_dir: None,
+            scratch_dir_lock: None,
+            cleanup_scratch_dir_on_start: None,
+            delegation_token_store_class: None,
+            delegation_token_store_zookeeper_connect_string: None,
+            support_concurrency: None,
+            txn_manager: None,
+            transactional_event_listeners: None,
+            compactor_initiator_on: None,
+            compactor_worker_threads: None,
+            dml_events: None,
+            notifications_add_thrift_objects: None,
+            event_message_factory: None,
+            wait_for_database_timeout: None,
+            handler_retry_attempts: None,
+            handler_retry_interval: None,
+            client_failure_retries: None,
+            client_connect_retry_delay: None,
+            client_socket_lifetime: None,
+            initialize_column_info: None,
+            init_hooks: None,
+            end_function_listeners: None,
+            filter_hook: None,
+            partition_name_whitelist_pattern: None,
+            limit_partition_request: None,
+            raw_store_impl: None,
+            cached_raw_store_impl: None,
+            cached_raw_store_cache_update_frequency: None,
+            cached_raw_store_max_cache_memory: None,
+            sasl_enabled: None,
+            thrift_bind_host: None,
+            tcp_keepalive: None,
+            thrift_framed_transport_enabled: None,
+            umask_mode: None,
+            execute_setugi: None,
+            authorization_storage_checks: None,
+            command_wrapper: None,
+            working_dir: None,
+            pre_stop_command: None,
+            render_jvm_args: None,
+            jmx_exporter_enabled: None,
+            metrics_enabled: None,
+            jmx_remote_credentials_secret: None,
+            metrics_reporters: None,
+            metrics_file_location: None,
+            metrics_file_frequency: None,
             resources: ResourcesFragment {
                 cpu: CpuLimitsFragment {
                     min: Some(Quantity("250m".to_owned())),
@@ -357,15 +1251,136 @@ impl MetaStoreConfig {
                         storage_class: None,
                         selectors: None,
                     },
+                    additional_volumes: BTreeMap::new(),
+                    ephemeral: EphemeralStorageConfigFragment {
+                        request: None,
+                        limit: None,
+                    },
+                    volume_claim_retention: VolumeClaimRetentionConfigFragment {
+                        when_scaled: None,
+                        when_deleted: None,
+                    },
                 },
             },
+            memory_request: None,
             logging: product_logging::spec::default_logging(),
+            log_volume: LogVolumeConfigFragment {
+                max_files_size: None,
+                medium: None,
+            },
             affinity: get_affinity(cluster_name, role),
+            pod_annotations: BTreeMap::new(),
+            security_context: SecurityContextConfigFragment {
+                fs_group_change_policy: None,
+                read_only_root_filesystem: None,
+                run_as_user: None,
+                run_as_group: None,
+            },
+            automount_service_account_token: None,
+            min_ready_seconds: None,
+            update_strategy: None,
+            pod_management_policy: None,
+            termination_message_policy: Some(TerminationMessagePolicy::FallbackToLogsOnError),
+            termination_message_path: None,
+            readiness_check: None,
+            image: None,
+            autoscaling: AutoscalingConfigFragment {
+                min_replicas: None,
+                max_replicas: None,
+                target_cpu_utilization: None,
+                target_memory_utilization: None,
+            },
             graceful_shutdown_timeout: Some(DEFAULT_METASTORE_GRACEFUL_SHUTDOWN_TIMEOUT),
         }
     }
 }
 
+/// Replaces the `{label:<key>}` references in the warehouse directory `dir` with the values of the
+/// labels
+fn substitute_labels(dir: &str, labels: &BTreeMap<String, String>) -> Result<String, Error> {
+    let mut substituted = String::new();
+    let mut rest = dir;
+    while let Some((before, after)) = rest.split_once("{label:") {
+        let (label, after) = after.split_once('}').unwrap_or((after, ""));
+        let value = labels
+            .get(label)
+            .with_context(|| MissingWarehouseDirLabelSnafu { dir, label })?;
+        substituted.push_str(before);
+        substituted.push_str(value);
+        rest = after;
+    }
+    substituted.push_str(rest);
+
+    Ok(substituted)
+}
+
+/// The managed and external warehouse directories, where explicitly configured directories take
+/// precedence over the ones derived from the template
+fn warehouse_dirs(
+    warehouse_dir: Option<&str>,
+    warehouse_external_dir: Option<&str>,
+    warehouse_dir_template: Option<&str>,
+) -> (Option<String>, Option<String>) {
+    let from_template = |dir_type: &str| {
+        warehouse_dir_template.map(|template| template.replace("{type}", dir_type))
+    };
+
+    (
+        warehouse_dir
+            .map(str::to_string)
+            .or_else(|| from_template("managed")),
+        warehouse_external_dir
+            .map(str::to_string)
+            .or_else(|| from_template("external")),
+    )
+}
+
+/// A list of values, e.g. fully qualified class names, which is written as comma separated list to
+/// the config. The list is replaced as a whole when merging the role and role group config.
+#[derive(Clone, Debug, Default, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct CommaSeparatedList<T = String>(pub Vec<T>);
+
+impl<T: Clone> Atomic for CommaSeparatedList<T> {}
+
+impl<T: std::fmt::Display> std::fmt::Display for CommaSeparatedList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let values = self.0.iter().map(T::to_string).collect::<Vec<_>>();
+        f.write_str(&values.join(","))
+    }
+}
+
+/// The native metrics reporters of the metastore. They are displayed with the names of the
+/// `hive.service.metrics.reporter` setting.
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum MetricsReporter {
+    /// Writes the metrics as JSON to the `metricsFileLocation`.
+    JsonFile,
+
+    /// Exposes the metrics as JMX MBeans.
+    Jmx,
+
+    /// Logs the metrics to the console.
+    Console,
+
+    /// Publishes the metrics to the Hadoop metrics2 system.
+    Hadoop2,
+}
+
+impl MetricsReporter {
+    /// The name of the reporter in the `metastore.metrics.reporters` setting of Hive 4
+    pub fn metastore_name(&self) -> &'static str {
+        match self {
+            MetricsReporter::JsonFile => "json",
+            MetricsReporter::Jmx => "jmx",
+            MetricsReporter::Console => "console",
+            MetricsReporter::Hadoop2 => "hadoop",
+        }
+    }
+}
+
 // TODO: Temporary solution until listener-operator is finished
 #[derive(Clone, Debug, Display, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
 #[serde(rename_all = "PascalCase")]
@@ -406,6 +1421,17 @@ pub enum DbType {
 }
 
 impl DbType {
+    /// The port the database listens on if none is given in the connection string
+    pub fn default_port(&self) -> Option<u16> {
+        match self {
+            DbType::Derby => None,
+            DbType::Mysql => Some(3306),
+            DbType::Postgres => Some(5432),
+            DbType::Mssql => Some(1433),
+            DbType::Oracle => Some(1521),
+        }
+    }
+
     pub fn get_jdbc_driver_class(&self) -> &str {
         match self {
             DbType::Derby => "org.apache.derby.jdbc.EmbeddedDriver",
@@ -432,11 +1458,93 @@ pub struct DatabaseConnectionSpec {
 
     /// A reference to a Secret containing the database credentials.
     /// The Secret needs to contain the keys `username` and `password`.
-    pub credentials_secret: String,
+    /// Either this or `credentialsCsiVolume` has to be set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials_secret: Option<String>,
+
+    /// A CSI volume providing the database credentials as the files `username` and `password`,
+    /// e.g. from the Secrets Store CSI driver. Either this or `credentialsSecret` has to be set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials_csi_volume: Option<CredentialsCsiVolume>,
+
+    /// Additional parameters appended to the connection string, e.g. `sslmode: require`.
+    /// They are joined with `?` and `&`, or with `;` for `mssql` and `derby`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub connection_url_parameters: BTreeMap<String, String>,
 }
 
-impl Configuration for MetaStoreConfigFragment {
-    type Configurable = HiveCluster;
+/// A CSI volume, which is mounted read-only at [`DB_CREDENTIALS_DIR`] and has to contain the files
+/// `username` and `password`.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialsCsiVolume {
+    /// The name of the CSI driver. Defaults to `secrets-store.csi.k8s.io`.
+    #[serde(default = "CredentialsCsiVolume::default_driver")]
+    pub driver: String,
+
+    /// Attributes passed to the CSI driver, e.g. `secretProviderClass: hive-db-credentials`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub volume_attributes: BTreeMap<String, String>,
+}
+
+impl CredentialsCsiVolume {
+    fn default_driver() -> String {
+        "secrets-store.csi.k8s.io".to_string()
+    }
+}
+
+impl DatabaseConnectionSpec {
+    /// The connection string with the additional parameters appended, written to the
+    /// `javax.jdo.option.ConnectionURL` setting
+    pub fn connection_url(&self) -> String {
+        let mut connection_url = self.conn_string.clone();
+
+        for (key, value) in &self.connection_url_parameters {
+            let separator = match self.db_type {
+                DbType::Derby | DbType::Mssql => ';',
+                DbType::Mysql | DbType::Postgres | DbType::Oracle => {
+                    if connection_url.contains('?') {
+                        '&'
+                    } else {
+                        '?'
+                    }
+                }
+            };
+            connection_url.push(separator);
+            connection_url.push_str(&format!("{key}={value}"));
+        }
+
+        connection_url
+    }
+
+    /// Extracts the host and port of the database server from the connection string, e.g.
+    /// `jdbc:postgresql://postgresql:5432/hive`, `jdbc:sqlserver://mssql;databaseName=hive` or
+    /// `jdbc:oracle:thin:@oracle:1521:hive`.
+    /// Returns `None` for the embedded Derby database and connection strings which can not be parsed.
+    pub fn host_and_port(&self) -> Option<(String, u16)> {
+        let default_port = self.db_type.default_port()?;
+        let (_, address) = self
+            .conn_string
+            .split_once("//")
+            .or_else(|| self.conn_string.split_once('@'))?;
+        let authority = address
+            .split(['/', ';', '?', ','])
+            .next()
+            .filter(|authority| !authority.is_empty())?;
+
+        let mut parts = authority.split(':');
+        let host = parts.next().filter(|host| !host.is_empty())?;
+        let port = match parts.next() {
+            Some(port) => port.parse().ok()?,
+            None => default_port,
+        };
+
+        Some((host.to_string(), port))
+    }
+}
+
+impl Configuration for MetaStoreConfigFragment {
+    type Configurable = HiveCluster;
 
     fn compute_env(
         &self,
@@ -445,10 +1553,29 @@ impl Configuration for MetaStoreConfigFragment {
     ) -> Result<BTreeMap<String, Option<String>>, product_config_utils::Error> {
         let mut result = BTreeMap::new();
 
+        let jmx_exporter_enabled = with_role_fallback(hive, &self.jmx_exporter_enabled, |config| {
+            &config.jmx_exporter_enabled
+        })
+        .unwrap_or(true);
+        let java_agent = if jmx_exporter_enabled {
+            format!("-javaagent:/stackable/jmx/jmx_prometheus_javaagent.jar={METRICS_PORT}:/stackable/jmx/jmx_hive_config.yaml ")
+        } else {
+            String::new()
+        };
+
+        let jmx_remote_enabled =
+            with_role_fallback(hive, &self.jmx_remote_credentials_secret, |config| {
+                &config.jmx_remote_credentials_secret
+            })
+            .is_some();
+        let jmx_remote = if jmx_remote_enabled {
+            jmx_remote_opts()
+        } else {
+            String::new()
+        };
+
         let env = formatdoc! {"
-            -javaagent:/stackable/jmx/jmx_prometheus_javaagent.jar={METRICS_PORT}:/stackable/jmx/jmx_hive_config.yaml \
-            -Djavax.net.ssl.trustStore={STACKABLE_TRUST_STORE} \
-            -Djavax.net.ssl.trustStorePassword={STACKABLE_TRUST_STORE_PASSWORD} \
+            {java_agent}{jmx_remote}-Djavax.net.ssl.trustStore={STACKABLE_TRUST_STORE} \
             -Djavax.net.ssl.trustStoreType=pkcs12 \
             -Djava.security.properties={STACKABLE_CONFIG_DIR}/{JVM_SECURITY_PROPERTIES_FILE} \
             {java_security_krb5_conf}",
@@ -478,15 +1605,245 @@ impl Configuration for MetaStoreConfigFragment {
 
         match file {
             HIVE_SITE_XML => {
-                if let Some(warehouse_dir) = &self.warehouse_dir {
+                // The warehouse directories, the transaction manager and the transactional event
+                // listeners depend on several settings, which may be spread over the role and
+                // the role group. They are derived from the merged config instead, see
+                // `MetaStoreConfig::derived_hive_site_properties`.
+                if let Some(tenant_colocation) = self.warehouse_tenant_colocation {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_WAREHOUSE_TENANT_COLOCATION.to_string(),
+                        Some(tenant_colocation.to_string()),
+                    );
+                }
+                if let Some(scratch_dir) = &self.scratch_dir {
+                    result.insert(
+                        MetaStoreConfig::SCRATCH_DIR.to_string(),
+                        Some(scratch_dir.to_string()),
+                    );
+                }
+                if let Some(scratch_dir_lock) = self.scratch_dir_lock {
+                    result.insert(
+                        MetaStoreConfig::SCRATCH_DIR_LOCK.to_string(),
+                        Some(scratch_dir_lock.to_string()),
+                    );
+                }
+                if let Some(cleanup_on_start) = self.cleanup_scratch_dir_on_start {
+                    result.insert(
+                        MetaStoreConfig::START_CLEANUP_SCRATCH_DIR.to_string(),
+                        Some(cleanup_on_start.to_string()),
+                    );
+                }
+                if let Some(token_store_class) = &self.delegation_token_store_class {
+                    result.insert(
+                        MetaStoreConfig::DELEGATION_TOKEN_STORE_CLASS.to_string(),
+                        Some(token_store_class.to_string()),
+                    );
+                }
+                if let Some(connect_string) = &self.delegation_token_store_zookeeper_connect_string
+                {
+                    result.insert(
+                        MetaStoreConfig::DELEGATION_TOKEN_STORE_ZOOKEEPER_CONNECT_STRING
+                            .to_string(),
+                        Some(connect_string.to_string()),
+                    );
+                }
+                if let Some(support_concurrency) = self.support_concurrency {
+                    result.insert(
+                        MetaStoreConfig::SUPPORT_CONCURRENCY.to_string(),
+                        Some(support_concurrency.to_string()),
+                    );
+                }
+                if let Some(initiator_on) = self.compactor_initiator_on {
+                    result.insert(
+                        MetaStoreConfig::COMPACTOR_INITIATOR_ON.to_string(),
+                        Some(initiator_on.to_string()),
+                    );
+                }
+                if let Some(worker_threads) = self.compactor_worker_threads {
+                    result.insert(
+                        MetaStoreConfig::COMPACTOR_WORKER_THREADS.to_string(),
+                        Some(worker_threads.to_string()),
+                    );
+                }
+                if let Some(dml_events) = self.dml_events {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_DML_EVENTS.to_string(),
+                        Some(dml_events.to_string()),
+                    );
+                }
+                if let Some(add_thrift_objects) = self.notifications_add_thrift_objects {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_NOTIFICATIONS_ADD_THRIFT_OBJECTS.to_string(),
+                        Some(add_thrift_objects.to_string()),
+                    );
+                }
+                if let Some(event_message_factory) = &self.event_message_factory {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_EVENT_MESSAGE_FACTORY.to_string(),
+                        Some(event_message_factory.to_string()),
+                    );
+                }
+                if let Some(retry_attempts) = self.handler_retry_attempts {
+                    result.insert(
+                        MetaStoreConfig::HMS_HANDLER_RETRY_ATTEMPTS.to_string(),
+                        Some(retry_attempts.to_string()),
+                    );
+                }
+                if let Some(retry_interval) = &self.handler_retry_interval {
+                    // Hive does not understand compound durations like `1m30s`
+                    result.insert(
+                        MetaStoreConfig::HMS_HANDLER_RETRY_INTERVAL.to_string(),
+                        Some(format!("{}ms", retry_interval.as_millis())),
+                    );
+                }
+                if let Some(failure_retries) = self.client_failure_retries {
+                    result.insert(
+                        MetaStoreConfig::CLIENT_FAILURE_RETRIES.to_string(),
+                        Some(failure_retries.to_string()),
+                    );
+                }
+                if let Some(retry_delay) = &self.client_connect_retry_delay {
+                    result.insert(
+                        MetaStoreConfig::CLIENT_CONNECT_RETRY_DELAY.to_string(),
+                        Some(format!("{}ms", retry_delay.as_millis())),
+                    );
+                }
+                if let Some(socket_lifetime) = &self.client_socket_lifetime {
+                    result.insert(
+                        MetaStoreConfig::CLIENT_SOCKET_LIFETIME.to_string(),
+                        Some(format!("{}ms", socket_lifetime.as_millis())),
+                    );
+                }
+                if let Some(initialize_column_info) = self.initialize_column_info {
+                    result.insert(
+                        MetaStoreConfig::DATANUCLEUS_INITIALIZE_COLUMN_INFO.to_string(),
+                        Some(initialize_column_info.to_string()),
+                    );
+                }
+                if let Some(init_hooks) = &self.init_hooks {
                     result.insert(
-                        MetaStoreConfig::METASTORE_WAREHOUSE_DIR.to_string(),
-                        Some(warehouse_dir.to_string()),
+                        MetaStoreConfig::METASTORE_INIT_HOOKS.to_string(),
+                        Some(init_hooks.to_string()),
+                    );
+                }
+                if let Some(end_function_listeners) = &self.end_function_listeners {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_END_FUNCTION_LISTENERS.to_string(),
+                        Some(end_function_listeners.to_string()),
+                    );
+                }
+                if let Some(filter_hook) = &self.filter_hook {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_FILTER_HOOK.to_string(),
+                        Some(filter_hook.to_string()),
+                    );
+                }
+                if let Some(pattern) = &self.partition_name_whitelist_pattern {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_PARTITION_NAME_WHITELIST_PATTERN.to_string(),
+                        Some(pattern.to_string()),
+                    );
+                }
+                if let Some(limit) = self.limit_partition_request {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_LIMIT_PARTITION_REQUEST.to_string(),
+                        Some(limit.to_string()),
+                    );
+                }
+                if let Some(raw_store_impl) = &self.raw_store_impl {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_RAW_STORE_IMPL.to_string(),
+                        Some(raw_store_impl.to_string()),
+                    );
+                }
+                if let Some(cached_raw_store_impl) = &self.cached_raw_store_impl {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_CACHED_RAW_STORE_IMPL.to_string(),
+                        Some(cached_raw_store_impl.to_string()),
+                    );
+                }
+                if let Some(update_frequency) = &self.cached_raw_store_cache_update_frequency {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_CACHED_RAW_STORE_CACHE_UPDATE_FREQUENCY
+                            .to_string(),
+                        Some(format!("{}ms", update_frequency.as_millis())),
+                    );
+                }
+                if let Some(max_cache_memory) = &self.cached_raw_store_max_cache_memory {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_CACHED_RAW_STORE_MAX_CACHE_MEMORY.to_string(),
+                        Some(max_cache_memory.to_string()),
+                    );
+                }
+                if let Some(execute_setugi) = self.execute_setugi {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_EXECUTE_SETUGI.to_string(),
+                        Some(execute_setugi.to_string()),
+                    );
+                }
+                if let Some(storage_checks) = self.authorization_storage_checks {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_AUTHORIZATION_STORAGE_CHECKS.to_string(),
+                        Some(storage_checks.to_string()),
+                    );
+                }
+                if let Some(thrift_bind_host) = &self.thrift_bind_host {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_THRIFT_BIND_HOST.to_string(),
+                        Some(thrift_bind_host.to_string()),
+                    );
+                }
+                if let Some(tcp_keepalive) = self.tcp_keepalive {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_SERVER_TCP_KEEPALIVE.to_string(),
+                        Some(tcp_keepalive.to_string()),
+                    );
+                }
+                if let Some(framed_transport) = self.thrift_framed_transport_enabled {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_THRIFT_FRAMED_TRANSPORT_ENABLED.to_string(),
+                        Some(framed_transport.to_string()),
+                    );
+                }
+                // Overrides the default set by the operator if Kerberos is enabled
+                if let Some(sasl_enabled) = self.sasl_enabled {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_SASL_ENABLED.to_string(),
+                        Some(sasl_enabled.to_string()),
+                    );
+                }
+                if let Some(metrics_reporters) = &self.metrics_reporters {
+                    result.insert(
+                        MetaStoreConfig::METASTORE_METRICS_REPORTERS.to_string(),
+                        Some(
+                            metrics_reporters
+                                .0
+                                .iter()
+                                .map(MetricsReporter::metastore_name)
+                                .collect::<Vec<_>>()
+                                .join(","),
+                        ),
+                    );
+                    result.insert(
+                        MetaStoreConfig::METRICS_REPORTER.to_string(),
+                        Some(metrics_reporters.to_string()),
+                    );
+                }
+                if let Some(metrics_file_location) = &self.metrics_file_location {
+                    result.insert(
+                        MetaStoreConfig::METRICS_FILE_LOCATION.to_string(),
+                        Some(metrics_file_location.to_string()),
+                    );
+                }
+                if let Some(metrics_file_frequency) = &self.metrics_file_frequency {
+                    result.insert(
+                        MetaStoreConfig::METRICS_FILE_FREQUENCY.to_string(),
+                        Some(format!("{}ms", metrics_file_frequency.as_millis())),
                     );
                 }
                 result.insert(
                     MetaStoreConfig::CONNECTION_URL.to_string(),
-                    Some(hive.spec.cluster_config.database.conn_string.clone()),
+                    Some(hive.spec.cluster_config.database.connection_url()),
                 );
                 // use a placeholder that will be replaced in the start command (also for the password)
                 result.insert(
@@ -502,11 +1859,23 @@ impl Configuration for MetaStoreConfigFragment {
                     Some(hive.db_type().get_jdbc_driver_class().to_string()),
                 );
 
+                let metrics_enabled = with_role_fallback(hive, &self.metrics_enabled, |config| {
+                    &config.metrics_enabled
+                })
+                .unwrap_or(true);
                 result.insert(
                     MetaStoreConfig::METASTORE_METRICS_ENABLED.to_string(),
-                    Some("true".to_string()),
+                    Some(metrics_enabled.to_string()),
                 );
             }
+            CORE_SITE_XML => {
+                if let Some(umask_mode) = &self.umask_mode {
+                    result.insert(
+                        MetaStoreConfig::FS_PERMISSIONS_UMASK_MODE.to_string(),
+                        Some(umask_mode.to_string()),
+                    );
+                }
+            }
             HIVE_ENV_SH => {}
             _ => {}
         }
@@ -515,6 +1884,35 @@ impl Configuration for MetaStoreConfigFragment {
     }
 }
 
+/// Role group settings are computed separately from the role settings, so the role setting has to
+/// be used as fallback for settings which are not written to a config file as is.
+fn with_role_fallback<T: Clone>(
+    hive: &HiveCluster,
+    value: &Option<T>,
+    setting: impl Fn(&MetaStoreConfigFragment) -> &Option<T>,
+) -> Option<T> {
+    value.clone().or_else(|| {
+        hive.spec
+            .metastore
+            .as_ref()
+            .and_then(|role| setting(&role.config.config).clone())
+    })
+}
+
+/// Opens the authenticated JMX remote port, e.g. to take thread dumps with `jconsole` via
+/// `kubectl port-forward`. The password and access files are created by the container command.
+fn jmx_remote_opts() -> String {
+    formatdoc! {"
+        -Dcom.sun.management.jmxremote.port={JMX_REMOTE_PORT} \
+        -Dcom.sun.management.jmxremote.rmi.port={JMX_REMOTE_PORT} \
+        -Dcom.sun.management.jmxremote.authenticate=true \
+        -Dcom.sun.management.jmxremote.ssl=false \
+        -Dcom.sun.management.jmxremote.password.file={STACKABLE_CONFIG_DIR}/{JMX_REMOTE_PASSWORD_FILE} \
+        -Dcom.sun.management.jmxremote.access.file={STACKABLE_CONFIG_DIR}/{JMX_REMOTE_ACCESS_FILE} \
+        -Djava.rmi.server.hostname=127.0.0.1 "
+    }
+}
+
 fn java_security_krb5_conf(hive: &HiveCluster) -> String {
     if hive.has_kerberos_enabled() {
         return formatdoc! {
@@ -533,6 +1931,18 @@ pub struct HiveClusterStatus {
     pub discovery_hash: Option<String>,
     #[serde(default)]
     pub conditions: Vec<ClusterCondition>,
+    /// The readiness of the metastore role groups, keyed by role group name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rolegroups: BTreeMap<String, RolegroupStatus>,
+}
+
+#[derive(Clone, Default, Debug, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RolegroupStatus {
+    /// The number of replicas the role group StatefulSet should run.
+    pub desired_replicas: i32,
+    /// The number of replicas of the role group StatefulSet that are ready.
+    pub ready_replicas: i32,
 }
 
 impl HasStatusCondition for HiveCluster {
@@ -583,7 +1993,7 @@ impl HiveCluster {
             .flat_map(move |(rolegroup_name, rolegroup)| {
                 let rolegroup_ref = self.metastore_rolegroup_ref(rolegroup_name);
                 let ns = ns.clone();
-                (0..rolegroup.replicas.unwrap_or(0)).map(move |i| PodRef {
+                (0..rolegroup.replicas.unwrap_or(DEFAULT_ROLEGROUP_REPLICAS)).map(move |i| PodRef {
                     namespace: ns.clone(),
                     role_group_service_name: rolegroup_ref.object_name(),
                     pod_name: format!("{}-{}", rolegroup_ref.object_name(), i),
@@ -634,10 +2044,31 @@ impl HiveCluster {
             .cluster_config
             .authentication
             .as_ref()
-            .map(|a| &a.kerberos)
+            .and_then(|a| a.kerberos.as_ref())
             .map(|k| k.secret_class.clone())
     }
 
+    pub fn ldap_authentication(&self) -> Option<&LdapAuthenticationConfig> {
+        self.spec
+            .cluster_config
+            .authentication
+            .as_ref()
+            .and_then(|a| a.ldap.as_ref())
+    }
+
+    pub fn has_tls_authentication_enabled(&self) -> bool {
+        self.tls_authentication_secret_class().is_some()
+    }
+
+    pub fn tls_authentication_secret_class(&self) -> Option<String> {
+        self.spec
+            .cluster_config
+            .authentication
+            .as_ref()
+            .and_then(|a| a.tls.as_ref())
+            .map(|t| t.secret_class.clone())
+    }
+
     pub fn db_type(&self) -> &DbType {
         &self.spec.cluster_config.database.db_type
     }
@@ -668,7 +2099,16 @@ impl HiveCluster {
         conf_role_group.merge(&conf_role);
 
         tracing::debug!("Merged config: {:?}", conf_role_group);
-        fragment::validate(conf_role_group).context(FragmentValidationFailureSnafu)
+        let merged_config: MetaStoreConfig =
+            fragment::validate(conf_role_group).context(FragmentValidationFailureSnafu)?;
+        merged_config.validate()?;
+
+        let (managed, external) = merged_config.warehouse_dirs();
+        for dir in managed.iter().chain(external.iter()) {
+            substitute_labels(dir, self.labels())?;
+        }
+
+        Ok(merged_config)
     }
 }
 
@@ -691,3 +2131,947 @@ impl PodRef {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::rstest;
+
+    use crate::test_util::hive_yaml;
+
+    /// Computes the `hive-site.xml` properties for a [`HiveCluster`] with the given metastore role `config`
+    fn compute_hive_site(config: &str) -> BTreeMap<String, Option<String>> {
+        compute_file(config, HIVE_SITE_XML)
+    }
+
+    fn compute_file(config: &str, file: &str) -> BTreeMap<String, Option<String>> {
+        let hive = hive_with_metastore_config(config);
+        let role = hive.role(&HiveRole::MetaStore).unwrap();
+
+        role.config
+            .config
+            .compute_files(&hive, "metastore", file)
+            .unwrap()
+    }
+
+    fn compute_hadoop_opts(config: &str) -> String {
+        let hive = hive_with_metastore_config(config);
+        let role = hive.role(&HiveRole::MetaStore).unwrap();
+
+        role.config
+            .config
+            .compute_env(&hive, "metastore")
+            .unwrap()
+            .remove(HADOOP_OPTS)
+            .flatten()
+            .expect("HADOOP_OPTS must be set")
+    }
+
+    fn merged_metastore_config(config: &str) -> Result<MetaStoreConfig, Error> {
+        let hive = hive_with_metastore_config(config);
+        hive.merged_config(
+            &HiveRole::MetaStore,
+            &HiveRole::MetaStore.rolegroup_ref(&hive, "default"),
+        )
+    }
+
+    /// Derives the `hive-site.xml` properties from the merged config of a [`HiveCluster`] with the
+    /// given metastore role `config` and `role_group_config`
+    fn derived_hive_site(config: &str, role_group_config: &str) -> BTreeMap<String, String> {
+        let mut hive = metastore_yaml(config);
+        hive["spec"]["metastore"]["roleGroups"]["default"]["config"] =
+            serde_yaml::from_str(role_group_config).expect("illegal test config");
+        let hive: HiveCluster = serde_yaml::from_value(hive).unwrap();
+
+        hive.merged_config(
+            &HiveRole::MetaStore,
+            &HiveRole::MetaStore.rolegroup_ref(&hive, "default"),
+        )
+        .unwrap()
+        .derived_hive_site_properties(&hive)
+    }
+
+    /// Parses a [`HiveCluster`] with a single role group and the given metastore role `config`
+    fn hive_with_metastore_config(config: &str) -> HiveCluster {
+        serde_yaml::from_value(metastore_yaml(config)).expect("illegal test input")
+    }
+
+    /// The manifest of [`hive_with_metastore_config`]
+    fn metastore_yaml(config: &str) -> serde_yaml::Value {
+        let mut hive = hive_yaml("metastore: {roleGroups: {default: {replicas: 1}}}");
+        hive["spec"]["metastore"]["config"] =
+            serde_yaml::from_str(config).expect("illegal test config");
+        hive
+    }
+
+    #[test]
+    fn test_pods_default_replicas() {
+        let mut hive = hive_with_metastore_config("{}");
+        let role_groups = &mut hive.spec.metastore.as_mut().unwrap().role_groups;
+        let mut scaled = role_groups["default"].clone();
+        scaled.replicas = Some(2);
+        role_groups.insert("scaled".to_string(), scaled);
+        role_groups.get_mut("default").unwrap().replicas = None;
+
+        // Role groups without replicas run a single Pod, like their StatefulSet
+        assert_eq!(
+            hive.pods()
+                .unwrap()
+                .map(|pod| pod.pod_name)
+                .collect::<Vec<_>>(),
+            vec![
+                "simple-hive-metastore-default-0",
+                "simple-hive-metastore-scaled-0",
+                "simple-hive-metastore-scaled-1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_graceful_shutdown_timeout() {
+        let input = metastore_yaml("gracefulShutdownTimeout: 5 minutes");
+
+        let err = serde_yaml::from_value::<HiveCluster>(input).unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("invalid gracefulShutdownTimeout \"5 minutes\""),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn test_graceful_shutdown_timeout() {
+        let merged_config = merged_metastore_config("gracefulShutdownTimeout: 1h30m").unwrap();
+
+        assert_eq!(
+            merged_config.graceful_shutdown_timeout,
+            Some(Duration::from_str("1h30m").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_delegation_token_store_settings() {
+        let hive_site = compute_hive_site(
+            r#"
+            delegationTokenStoreClass: org.apache.hadoop.hive.thrift.ZooKeeperTokenStore
+            delegationTokenStoreZookeeperConnectString: zk-1:2181,zk-2:2181/hive
+            "#,
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::DELEGATION_TOKEN_STORE_CLASS),
+            Some(&Some(
+                "org.apache.hadoop.hive.thrift.ZooKeeperTokenStore".to_string()
+            ))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::DELEGATION_TOKEN_STORE_ZOOKEEPER_CONNECT_STRING),
+            Some(&Some("zk-1:2181,zk-2:2181/hive".to_string()))
+        );
+    }
+
+    #[rstest]
+    #[case::acid(
+        "supportConcurrency: true",
+        "{}",
+        Some(MetaStoreConfig::DB_TXN_MANAGER)
+    )]
+    #[case::acid_disabled_on_role_group(
+        "supportConcurrency: true",
+        "supportConcurrency: false",
+        None
+    )]
+    #[case::custom_manager_on_role(
+        "txnManager: com.example.TxnManager",
+        "supportConcurrency: true",
+        Some("com.example.TxnManager")
+    )]
+    #[case::none("{}", "{}", None)]
+    fn test_txn_manager(
+        #[case] config: &str,
+        #[case] role_group_config: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let hive_site = derived_hive_site(config, role_group_config);
+
+        assert_eq!(
+            hive_site
+                .get(MetaStoreConfig::TXN_MANAGER)
+                .map(String::as_str),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::acid_default(
+        "supportConcurrency: true",
+        "{}",
+        Some("org.apache.hive.hcatalog.listener.DbNotificationListener")
+    )]
+    #[case::acid_appended(
+        r#"
+        supportConcurrency: true
+        transactionalEventListeners:
+          - com.example.ReplicationListener
+          - org.apache.hive.hcatalog.listener.DbNotificationListener
+        "#,
+        "{}",
+        Some("org.apache.hive.hcatalog.listener.DbNotificationListener,com.example.ReplicationListener")
+    )]
+    #[case::acid_on_role_listeners_on_role_group(
+        "supportConcurrency: true",
+        "transactionalEventListeners: [com.example.ReplicationListener]",
+        Some("org.apache.hive.hcatalog.listener.DbNotificationListener,com.example.ReplicationListener")
+    )]
+    #[case::listeners_on_role_acid_on_role_group(
+        "transactionalEventListeners: [com.example.ReplicationListener]",
+        "supportConcurrency: true",
+        Some("org.apache.hive.hcatalog.listener.DbNotificationListener,com.example.ReplicationListener")
+    )]
+    #[case::without_acid(
+        "transactionalEventListeners: [com.example.ReplicationListener]",
+        "{}",
+        Some("com.example.ReplicationListener")
+    )]
+    #[case::none("{}", "{}", None)]
+    fn test_transactional_event_listeners(
+        #[case] config: &str,
+        #[case] role_group_config: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let hive_site = derived_hive_site(config, role_group_config);
+
+        assert_eq!(
+            hive_site
+                .get(MetaStoreConfig::TRANSACTIONAL_EVENT_LISTENERS)
+                .map(String::as_str),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_compactor_settings() {
+        let hive_site = compute_hive_site(
+            r#"
+            compactorInitiatorOn: true
+            compactorWorkerThreads: 2
+            "#,
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::COMPACTOR_INITIATOR_ON),
+            Some(&Some("true".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::COMPACTOR_WORKER_THREADS),
+            Some(&Some("2".to_string()))
+        );
+
+        let hive_site = compute_hive_site("compactorInitiatorOn: false");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::COMPACTOR_INITIATOR_ON),
+            Some(&Some("false".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::COMPACTOR_WORKER_THREADS),
+            None
+        );
+    }
+
+    #[test]
+    fn test_warehouse_tenant_colocation() {
+        let hive_site = compute_hive_site("warehouseTenantColocation: true");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_WAREHOUSE_TENANT_COLOCATION),
+            Some(&Some("true".to_string()))
+        );
+
+        // An explicit `false` is passed on instead of falling back to the Hive default
+        let hive_site = compute_hive_site("warehouseTenantColocation: false");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_WAREHOUSE_TENANT_COLOCATION),
+            Some(&Some("false".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_warehouse_dir_label_substitution() {
+        let config = "warehouseDirTemplate: s3a://warehouse/{label:tenant}/{type}";
+        let mut hive = metastore_yaml(config);
+        hive["metadata"]["labels"]["tenant"] = "team-a".into();
+        let hive: HiveCluster = serde_yaml::from_value(hive).unwrap();
+
+        let hive_site = hive
+            .merged_config(
+                &HiveRole::MetaStore,
+                &HiveRole::MetaStore.rolegroup_ref(&hive, "default"),
+            )
+            .unwrap()
+            .derived_hive_site_properties(&hive);
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_WAREHOUSE_DIR),
+            Some(&"s3a://warehouse/team-a/managed".to_string())
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_WAREHOUSE_EXTERNAL_DIR),
+            Some(&"s3a://warehouse/team-a/external".to_string())
+        );
+
+        // The label is missing
+        assert!(matches!(
+            merged_metastore_config(config),
+            Err(Error::MissingWarehouseDirLabel { label, .. }) if label == "tenant"
+        ));
+    }
+
+    #[rstest]
+    #[case::template(
+        "warehouseDirTemplate: s3a://bucket/{type}/warehouse",
+        "{}",
+        "s3a://bucket/managed/warehouse"
+    )]
+    #[case::explicit_dir_wins(
+        "{warehouseDir: s3a://managed/warehouse, warehouseDirTemplate: s3a://bucket/{type}/warehouse}",
+        "{}",
+        "s3a://managed/warehouse"
+    )]
+    #[case::explicit_dir_on_role_wins_over_template_on_role_group(
+        "warehouseDir: s3a://managed/warehouse",
+        "warehouseDirTemplate: s3a://bucket/{type}/warehouse",
+        "s3a://managed/warehouse"
+    )]
+    fn test_warehouse_dir_template(
+        #[case] config: &str,
+        #[case] role_group_config: &str,
+        #[case] expected_managed: &str,
+    ) {
+        let hive_site = derived_hive_site(config, role_group_config);
+
+        assert_eq!(
+            hive_site
+                .get(MetaStoreConfig::METASTORE_WAREHOUSE_DIR)
+                .map(String::as_str),
+            Some(expected_managed)
+        );
+        assert_eq!(
+            hive_site
+                .get(MetaStoreConfig::METASTORE_WAREHOUSE_EXTERNAL_DIR)
+                .map(String::as_str),
+            Some("s3a://bucket/external/warehouse")
+        );
+    }
+
+    #[rstest]
+    #[case::template_without_placeholder("warehouseDirTemplate: s3a://bucket/warehouse", false)]
+    #[case::same_explicit_dirs(
+        "{warehouseDir: s3a://bucket/warehouse, warehouseExternalDir: s3a://bucket/warehouse}",
+        false
+    )]
+    #[case::template("warehouseDirTemplate: s3a://bucket/{type}/warehouse", true)]
+    #[case::none("{}", true)]
+    fn test_warehouse_dirs_must_differ(#[case] config: &str, #[case] valid: bool) {
+        let merged_config = merged_metastore_config(config);
+
+        match valid {
+            true => assert!(merged_config.is_ok()),
+            false => assert!(matches!(
+                merged_config,
+                Err(Error::WarehouseDirsNotDistinct { .. })
+            )),
+        }
+    }
+
+    #[test]
+    fn test_scratch_dir_settings() {
+        let hive_site = compute_hive_site(
+            r#"
+            scratchDir: /tmp/hive
+            scratchDirLock: true
+            cleanupScratchDirOnStart: true
+            "#,
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::SCRATCH_DIR),
+            Some(&Some("/tmp/hive".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::SCRATCH_DIR_LOCK),
+            Some(&Some("true".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::START_CLEANUP_SCRATCH_DIR),
+            Some(&Some("true".to_string()))
+        );
+
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(hive_site.get(MetaStoreConfig::SCRATCH_DIR), None);
+        assert_eq!(hive_site.get(MetaStoreConfig::SCRATCH_DIR_LOCK), None);
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::START_CLEANUP_SCRATCH_DIR),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dml_events() {
+        let hive_site = compute_hive_site("dmlEvents: true");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_DML_EVENTS),
+            Some(&Some("true".to_string()))
+        );
+
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(hive_site.get(MetaStoreConfig::METASTORE_DML_EVENTS), None);
+    }
+
+    #[test]
+    fn test_notifications_add_thrift_objects() {
+        let hive_site = compute_hive_site("notificationsAddThriftObjects: true");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_NOTIFICATIONS_ADD_THRIFT_OBJECTS),
+            Some(&Some("true".to_string()))
+        );
+
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_NOTIFICATIONS_ADD_THRIFT_OBJECTS),
+            None
+        );
+    }
+
+    #[test]
+    fn test_event_message_factory() {
+        let hive_site = compute_hive_site(&format!(
+            "eventMessageFactory: {}",
+            MetaStoreConfig::METASTORE_EVENT_MESSAGE_FACTORY_JSON
+        ));
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_EVENT_MESSAGE_FACTORY),
+            Some(&Some(
+                MetaStoreConfig::METASTORE_EVENT_MESSAGE_FACTORY_JSON.to_string()
+            ))
+        );
+
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_EVENT_MESSAGE_FACTORY),
+            None
+        );
+    }
+
+    #[test]
+    fn test_handler_retry_settings() {
+        let hive_site = compute_hive_site(
+            r#"
+            handlerRetryAttempts: 5
+            handlerRetryInterval: 1m30s
+            "#,
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::HMS_HANDLER_RETRY_ATTEMPTS),
+            Some(&Some("5".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::HMS_HANDLER_RETRY_INTERVAL),
+            Some(&Some("90000ms".to_string()))
+        );
+
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::HMS_HANDLER_RETRY_ATTEMPTS),
+            None
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::HMS_HANDLER_RETRY_INTERVAL),
+            None
+        );
+    }
+
+    #[test]
+    fn test_client_socket_lifetime() {
+        let hive_site = compute_hive_site("clientSocketLifetime: 10m");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::CLIENT_SOCKET_LIFETIME),
+            Some(&Some("600000ms".to_string()))
+        );
+
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(hive_site.get(MetaStoreConfig::CLIENT_SOCKET_LIFETIME), None);
+    }
+
+    #[test]
+    fn test_client_retry_settings() {
+        let hive_site = compute_hive_site(
+            r#"
+            clientFailureRetries: 3
+            clientConnectRetryDelay: 5s
+            "#,
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::CLIENT_FAILURE_RETRIES),
+            Some(&Some("3".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::CLIENT_CONNECT_RETRY_DELAY),
+            Some(&Some("5000ms".to_string()))
+        );
+
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(hive_site.get(MetaStoreConfig::CLIENT_FAILURE_RETRIES), None);
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::CLIENT_CONNECT_RETRY_DELAY),
+            None
+        );
+    }
+
+    #[rstest]
+    #[case("NONE", "NONE")]
+    #[case("PK", "PK")]
+    #[case("ALL", "ALL")]
+    fn test_initialize_column_info(#[case] configured: &str, #[case] expected: &str) {
+        let hive_site = compute_hive_site(&format!("initializeColumnInfo: {configured}"));
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::DATANUCLEUS_INITIALIZE_COLUMN_INFO),
+            Some(&Some(expected.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_jmx_exporter_disabled() {
+        let enabled = compute_hadoop_opts("{}");
+        let disabled = compute_hadoop_opts("jmxExporterEnabled: false");
+
+        assert!(enabled.starts_with("-javaagent:/stackable/jmx/jmx_prometheus_javaagent.jar"));
+        assert!(!disabled.contains("-javaagent"));
+        assert_eq!(
+            enabled.split_whitespace().skip(1).collect::<Vec<_>>(),
+            disabled.split_whitespace().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_metrics_enabled_per_rolegroup() {
+        let mut hive: serde_yaml::Value = metastore_yaml("metricsEnabled: false");
+        hive["spec"]["metastore"]["roleGroups"]["canary"] = serde_yaml::from_str(
+            r#"
+            replicas: 1
+            config:
+              metricsEnabled: true
+            "#,
+        )
+        .unwrap();
+        let hive: HiveCluster = serde_yaml::from_value(hive).unwrap();
+        let role = hive.role(&HiveRole::MetaStore).unwrap();
+
+        let metrics_enabled = |rolegroup_name: &str| {
+            role.role_groups[rolegroup_name]
+                .config
+                .config
+                .compute_files(&hive, "metastore", HIVE_SITE_XML)
+                .unwrap()
+                .remove(MetaStoreConfig::METASTORE_METRICS_ENABLED)
+                .flatten()
+        };
+
+        assert_eq!(metrics_enabled("canary").as_deref(), Some("true"));
+        assert_eq!(metrics_enabled("default").as_deref(), Some("false"));
+        assert_eq!(
+            compute_hive_site("{}")
+                .remove(MetaStoreConfig::METASTORE_METRICS_ENABLED)
+                .flatten()
+                .as_deref(),
+            Some("true")
+        );
+    }
+
+    #[test]
+    fn test_metrics_reporters() {
+        let hive_site = compute_hive_site(
+            r#"
+            metricsReporters:
+              - JSON_FILE
+              - JMX
+              - CONSOLE
+            "#,
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_METRICS_REPORTERS),
+            Some(&Some("json,jmx,console".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METRICS_REPORTER),
+            Some(&Some("JSON_FILE,JMX,CONSOLE".to_string()))
+        );
+
+        // Unknown reporters are rejected
+        assert!(
+            serde_yaml::from_str::<MetaStoreConfigFragment>("metricsReporters: [INFLUX]").is_err()
+        );
+    }
+
+    #[test]
+    fn test_json_file_metrics_reporter() {
+        let hive_site = compute_hive_site(
+            r#"
+            metricsReporters: [JSON_FILE]
+            metricsFileLocation: /stackable/log/metrics/metastore-metrics.json
+            metricsFileFrequency: 1m30s
+            "#,
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METRICS_REPORTER),
+            Some(&Some("JSON_FILE".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METRICS_FILE_LOCATION),
+            Some(&Some(
+                "/stackable/log/metrics/metastore-metrics.json".to_string()
+            ))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METRICS_FILE_FREQUENCY),
+            Some(&Some("90000ms".to_string()))
+        );
+
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(hive_site.get(MetaStoreConfig::METRICS_REPORTER), None);
+        assert_eq!(hive_site.get(MetaStoreConfig::METRICS_FILE_LOCATION), None);
+        assert_eq!(hive_site.get(MetaStoreConfig::METRICS_FILE_FREQUENCY), None);
+
+        // Only durations are accepted
+        assert!(
+            serde_yaml::from_str::<MetaStoreConfigFragment>("metricsFileFrequency: often").is_err()
+        );
+    }
+
+    #[test]
+    fn test_hooks_and_listeners() {
+        let hive_site = compute_hive_site(
+            r#"
+            initHooks:
+              - com.example.TracingInitHook
+            endFunctionListeners:
+              - com.example.MetricsListener
+              - com.example.AuditListener
+            "#,
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_INIT_HOOKS),
+            Some(&Some("com.example.TracingInitHook".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_END_FUNCTION_LISTENERS),
+            Some(&Some(
+                "com.example.MetricsListener,com.example.AuditListener".to_string()
+            ))
+        );
+
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(hive_site.get(MetaStoreConfig::METASTORE_INIT_HOOKS), None);
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_END_FUNCTION_LISTENERS),
+            None
+        );
+    }
+
+    #[test]
+    fn test_thrift_bind_host() {
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_THRIFT_BIND_HOST),
+            None
+        );
+
+        let hive_site = compute_hive_site("thriftBindHost: 0.0.0.0");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_THRIFT_BIND_HOST),
+            Some(&Some("0.0.0.0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_filter_hook() {
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(hive_site.get(MetaStoreConfig::METASTORE_FILTER_HOOK), None);
+
+        let hive_site = compute_hive_site(
+            "filterHook: org.apache.hadoop.hive.metastore.DefaultMetaStoreFilterHookImpl",
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_FILTER_HOOK),
+            Some(&Some(
+                "org.apache.hadoop.hive.metastore.DefaultMetaStoreFilterHookImpl".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_partition_name_whitelist_pattern() {
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_PARTITION_NAME_WHITELIST_PATTERN),
+            None
+        );
+
+        let hive_site = compute_hive_site(r#"partitionNameWhitelistPattern: '[\w\-=]*'"#);
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_PARTITION_NAME_WHITELIST_PATTERN),
+            Some(&Some(r"[\w\-=]*".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_hdfs_ownership_settings() {
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_EXECUTE_SETUGI),
+            None
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_AUTHORIZATION_STORAGE_CHECKS),
+            None
+        );
+
+        let hive_site = compute_hive_site(
+            r#"
+            executeSetugi: true
+            authorizationStorageChecks: true
+            "#,
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_EXECUTE_SETUGI),
+            Some(&Some("true".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_AUTHORIZATION_STORAGE_CHECKS),
+            Some(&Some("true".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_limit_partition_request() {
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_LIMIT_PARTITION_REQUEST),
+            None
+        );
+
+        let hive_site = compute_hive_site("limitPartitionRequest: 10000");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_LIMIT_PARTITION_REQUEST),
+            Some(&Some("10000".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_tcp_keepalive() {
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_SERVER_TCP_KEEPALIVE),
+            None
+        );
+
+        let hive_site = compute_hive_site("tcpKeepalive: true");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_SERVER_TCP_KEEPALIVE),
+            Some(&Some("true".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_thrift_framed_transport() {
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_THRIFT_FRAMED_TRANSPORT_ENABLED),
+            None
+        );
+
+        let hive_site = compute_hive_site("thriftFramedTransportEnabled: true");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_THRIFT_FRAMED_TRANSPORT_ENABLED),
+            Some(&Some("true".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_raw_store() {
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_RAW_STORE_IMPL),
+            None
+        );
+
+        let hive_site = compute_hive_site(
+            r#"
+            rawStoreImpl: org.apache.hadoop.hive.metastore.cache.CachedStore
+            cachedRawStoreCacheUpdateFrequency: 2m
+            cachedRawStoreMaxCacheMemory: 1Gb
+            "#,
+        );
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_RAW_STORE_IMPL),
+            Some(&Some(
+                "org.apache.hadoop.hive.metastore.cache.CachedStore".to_string()
+            ))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_CACHED_RAW_STORE_CACHE_UPDATE_FREQUENCY),
+            Some(&Some("120000ms".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_CACHED_RAW_STORE_MAX_CACHE_MEMORY),
+            Some(&Some("1Gb".to_string()))
+        );
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::METASTORE_CACHED_RAW_STORE_IMPL),
+            None
+        );
+    }
+
+    #[rstest]
+    #[case("compactorInitiatorOn: true", false)]
+    #[case("{ compactorInitiatorOn: true, compactorWorkerThreads: 0 }", false)]
+    #[case("{ compactorInitiatorOn: true, compactorWorkerThreads: 1 }", true)]
+    #[case("compactorInitiatorOn: false", true)]
+    #[case("{}", true)]
+    fn test_compactor_validation(#[case] config: &str, #[case] valid: bool) {
+        let merged_config = merged_metastore_config(config);
+
+        match valid {
+            true => assert!(merged_config.is_ok()),
+            false => assert!(matches!(
+                merged_config,
+                Err(Error::CompactorWithoutWorkerThreads)
+            )),
+        }
+    }
+
+    #[rstest]
+    #[case("securityContext: { runAsUser: 0 }", false)]
+    #[case("securityContext: { runAsUser: 0, runAsGroup: 1000 }", false)]
+    #[case("securityContext: { runAsUser: 1001, runAsGroup: 0 }", true)]
+    #[case("{}", true)]
+    fn test_run_as_root_validation(#[case] config: &str, #[case] valid: bool) {
+        let merged_config = merged_metastore_config(config);
+
+        match valid {
+            true => assert!(merged_config.is_ok()),
+            false => assert!(matches!(merged_config, Err(Error::RunAsRoot))),
+        }
+    }
+
+    #[rstest]
+    #[case("jdbc:postgresql://postgresql:5432/hive", DbType::Postgres, Some(("postgresql", 5432)))]
+    #[case("jdbc:postgresql://postgresql/hive", DbType::Postgres, Some(("postgresql", 5432)))]
+    #[case("jdbc:mysql://mysql:3307/hive?useSSL=false", DbType::Mysql, Some(("mysql", 3307)))]
+    #[case("jdbc:sqlserver://mssql;databaseName=hive", DbType::Mssql, Some(("mssql", 1433)))]
+    #[case("jdbc:oracle:thin:@oracle:1522:hive", DbType::Oracle, Some(("oracle", 1522)))]
+    #[case("jdbc:oracle:thin:@//oracle:1522/hive", DbType::Oracle, Some(("oracle", 1522)))]
+    #[case("jdbc:derby:;databaseName=/tmp/hive;create=true", DbType::Derby, None)]
+    #[case("jdbc:postgresql:hive", DbType::Postgres, None)]
+    fn test_database_host_and_port(
+        #[case] conn_string: &str,
+        #[case] db_type: DbType,
+        #[case] expected: Option<(&str, u16)>,
+    ) {
+        let database = DatabaseConnectionSpec {
+            conn_string: conn_string.to_string(),
+            db_type,
+            credentials_secret: Some("hive-credentials".to_string()),
+            credentials_csi_volume: None,
+            connection_url_parameters: BTreeMap::new(),
+        };
+
+        assert_eq!(
+            database.host_and_port(),
+            expected.map(|(host, port)| (host.to_string(), port))
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "jdbc:postgresql://postgresql:5432/hive",
+        DbType::Postgres,
+        "jdbc:postgresql://postgresql:5432/hive?ApplicationName=hive&sslmode=require"
+    )]
+    #[case(
+        "jdbc:mysql://mysql:3306/hive?useSSL=true",
+        DbType::Mysql,
+        "jdbc:mysql://mysql:3306/hive?useSSL=true&ApplicationName=hive&sslmode=require"
+    )]
+    #[case(
+        "jdbc:sqlserver://mssql;databaseName=hive",
+        DbType::Mssql,
+        "jdbc:sqlserver://mssql;databaseName=hive;ApplicationName=hive;sslmode=require"
+    )]
+    fn test_connection_url_parameters(
+        #[case] conn_string: &str,
+        #[case] db_type: DbType,
+        #[case] expected: &str,
+    ) {
+        let database = DatabaseConnectionSpec {
+            conn_string: conn_string.to_string(),
+            db_type,
+            credentials_secret: Some("hive-credentials".to_string()),
+            credentials_csi_volume: None,
+            connection_url_parameters: BTreeMap::from([
+                ("sslmode".to_string(), "require".to_string()),
+                ("ApplicationName".to_string(), "hive".to_string()),
+            ]),
+        };
+
+        assert_eq!(database.connection_url(), expected);
+    }
+
+    #[test]
+    fn test_connection_url_without_parameters() {
+        let hive_site = compute_hive_site("{}");
+
+        assert_eq!(
+            hive_site.get(MetaStoreConfig::CONNECTION_URL),
+            Some(&Some("jdbc:postgresql://postgresql:5432/hive".to_string()))
+        );
+    }
+}
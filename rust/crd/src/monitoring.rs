@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use stackable_operator::schemars::{self, JsonSchema};
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitoringConfig {
+    /// Settings for the Prometheus Operator `ServiceMonitor`.
+    #[serde(default)]
+    pub service_monitor: ServiceMonitorConfig,
+
+    /// Settings for the `<rolegroup>-metrics` Services exposing the metrics port.
+    #[serde(default)]
+    pub metrics_service: MetricsServiceConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceMonitorConfig {
+    /// Whether a `ServiceMonitor` scraping the metrics of all metastore Pods should be created.
+    /// This requires the [Prometheus Operator](https://prometheus-operator.dev/) to be installed.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Additional labels to add to the `ServiceMonitor`, e.g. to match the `serviceMonitorSelector`
+    /// of the Prometheus instance.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsServiceConfig {
+    /// Whether a headless `<rolegroup>-metrics` Service exposing only the metrics port should be
+    /// created for every role group. Defaults to `true`.
+    /// The metrics port is still exposed by the role group Service if it is disabled.
+    #[serde(default = "MetricsServiceConfig::default_enabled")]
+    pub enabled: bool,
+}
+
+impl MetricsServiceConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+}
+
+impl Default for MetricsServiceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+        }
+    }
+}
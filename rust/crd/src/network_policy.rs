@@ -0,0 +1,24 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use stackable_operator::schemars::{self, JsonSchema};
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPolicyConfig {
+    /// Whether a `NetworkPolicy` restricting the ingress of the metastore Pods to the Hive and
+    /// metrics ports should be created. Defaults to `false`.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Labels of the Pods which are allowed to connect to the metastore, e.g.
+    /// `hive-client: "true"`. If empty, all Pods of the client namespaces are allowed.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub client_labels: BTreeMap<String, String>,
+
+    /// Labels of the namespaces the clients are running in, e.g.
+    /// `kubernetes.io/metadata.name: trino`. An empty map allows clients in all namespaces.
+    /// If not set, only clients in the namespace of the HiveCluster are allowed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_namespace_labels: Option<BTreeMap<String, String>>,
+}
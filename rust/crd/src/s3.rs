@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use stackable_operator::schemars::{self, JsonSchema};
+use strum::Display;
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3TuningConfig {
+    /// How often a failed S3 request is attempted, e.g. to cope with flaky S3 endpoints.
+    /// Maps to the `fs.s3a.attempts.maximum` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts_maximum: Option<u32>,
+
+    /// How often S3A retries failed operations, e.g. due to throttling.
+    /// Maps to the `fs.s3a.retry.limit` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_limit: Option<u32>,
+
+    /// The AWS region of the buckets, e.g. `eu-central-1`, written as `fs.s3a.endpoint.region`.
+    /// If no S3 connection is configured, the endpoint is derived from the region as
+    /// `s3.<region>.amazonaws.com`, e.g. to use AWS S3 with credentials from the environment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    /// Name of a Secret containing the keys `endpoint` and `region`, e.g. for deployments which
+    /// keep them confidential. They are passed to the metastore container as environment
+    /// variables, so they do not appear in the ConfigMaps, and take precedence over the endpoint
+    /// of the S3 connection and the `region`.
+    /// Maps to the `fs.s3a.endpoint` and `fs.s3a.endpoint.region` settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint_secret: Option<String>,
+
+    /// Forces `fs.s3a.path.style.access`, regardless of the access style of the S3 connection,
+    /// e.g. for S3 compatible stores behind a gateway routing requests by path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_style_access: Option<bool>,
+
+    /// The comma separated chain of AWS credentials providers used by S3A, e.g.
+    /// `com.amazonaws.auth.WebIdentityTokenCredentialsProvider`. It is written verbatim and takes
+    /// precedence over the providers S3A selects by default.
+    /// Maps to the `fs.s3a.aws.credentials.provider` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials_provider: Option<String>,
+
+    /// The canned ACL applied to the objects written by S3A, e.g. `BucketOwnerFullControl` for
+    /// writes to buckets of another AWS account. Maps to the `fs.s3a.canned.acl` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canned_acl: Option<String>,
+
+    /// The server-side encryption of the objects written by S3A, e.g. `SSE-KMS` for buckets
+    /// which require encryption with a KMS key.
+    /// Maps to the `fs.s3a.server-side-encryption-algorithm` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_side_encryption_algorithm: Option<S3ServerSideEncryptionAlgorithm>,
+
+    /// The ID or ARN of the KMS key used for `SSE-KMS`. If not set, the default KMS key of the
+    /// account is used. Maps to the `fs.s3a.server-side-encryption.key` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_side_encryption_key: Option<String>,
+
+    /// How S3A reacts to objects changing while they are read, e.g. `none` or `warn` for S3
+    /// compatible stores with weak consistency, which would otherwise fail the reads.
+    /// Maps to the `fs.s3a.change.detection.mode` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_detection_mode: Option<S3ChangeDetectionMode>,
+
+    /// Which attribute of an object is used to detect changes, either `etag` or `versionid`.
+    /// Maps to the `fs.s3a.change.detection.source` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_detection_source: Option<S3ChangeDetectionSource>,
+
+    /// The HTTP proxy S3A connects to S3 through, e.g. in networks without direct egress.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<S3ProxyConfig>,
+
+    /// S3A settings which only apply to a single bucket, keyed by bucket name.
+    /// The settings are given without the `fs.s3a.` prefix, e.g. `endpoint` or `path.style.access`,
+    /// and are written as `fs.s3a.bucket.<bucket>.<setting>` to the `hive-site.xml`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bucket_overrides: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3ProxyConfig {
+    /// The host of the proxy. Maps to the `fs.s3a.proxy.host` setting.
+    pub host: String,
+
+    /// The port of the proxy. Maps to the `fs.s3a.proxy.port` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+
+    /// Name of a Secret containing the keys `username` and `password` to authenticate against the
+    /// proxy. Maps to the `fs.s3a.proxy.username` and `fs.s3a.proxy.password` settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials_secret: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+pub enum S3ServerSideEncryptionAlgorithm {
+    /// Encryption with keys managed by S3.
+    #[serde(rename = "AES256")]
+    #[strum(serialize = "AES256")]
+    SseS3,
+
+    /// Encryption with a key managed by the AWS Key Management Service.
+    #[serde(rename = "SSE-KMS")]
+    #[strum(serialize = "SSE-KMS")]
+    SseKms,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum S3ChangeDetectionMode {
+    /// The change is detected by the store, which rejects requests for a changed object.
+    Server,
+
+    /// The change is detected by S3A, which compares the attribute of each response.
+    Client,
+
+    /// A detected change is only logged.
+    Warn,
+
+    /// Changes are not detected.
+    None,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum S3ChangeDetectionSource {
+    /// The ETag of the object.
+    Etag,
+
+    /// The version ID of the object, which requires a versioned bucket.
+    VersionId,
+}
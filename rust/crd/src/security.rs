@@ -1,11 +1,26 @@
 use serde::{Deserialize, Serialize};
-use stackable_operator::schemars::{self, JsonSchema};
+use stackable_operator::{
+    config::{
+        fragment::Fragment,
+        merge::{Atomic, Merge},
+    },
+    schemars::{self, JsonSchema},
+};
+use strum::Display;
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, JsonSchema, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AuthenticationConfig {
     /// Kerberos configuration.
-    pub kerberos: KerberosConfig,
+    pub kerberos: Option<KerberosConfig>,
+
+    /// Mutual TLS configuration. The metastore serves TLS and requires clients to present a
+    /// certificate signed by the CA of the given SecretClass.
+    pub tls: Option<TlsAuthenticationConfig>,
+
+    /// LDAP configuration. Clients authenticate with their LDAP username and password.
+    /// Only supported by Hive 4 and newer.
+    pub ldap: Option<LdapAuthenticationConfig>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, JsonSchema, PartialEq, Serialize)]
@@ -13,4 +28,95 @@ pub struct AuthenticationConfig {
 pub struct KerberosConfig {
     /// Name of the SecretClass providing the keytab for the HBase services.
     pub secret_class: String,
+
+    /// Whether the operator generates a `core-site.xml` setting `hadoop.security.authentication`
+    /// if no HDFS connection is configured, e.g. for S3. Defaults to `true`. Can be disabled if
+    /// the complete `core-site.xml` is provided with configuration overrides, which are applied
+    /// either way.
+    #[serde(default = "KerberosConfig::default_generate_core_site")]
+    pub generate_core_site: bool,
+}
+
+impl KerberosConfig {
+    fn default_generate_core_site() -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TlsAuthenticationConfig {
+    /// Name of the SecretClass providing the server certificate of the metastore as well as the
+    /// CA used to verify the client certificates.
+    pub secret_class: String,
 }
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LdapAuthenticationConfig {
+    /// The URL of the LDAP server, e.g. `ldaps://ldap.example.com:636`.
+    pub url: String,
+
+    /// The base DN of the users, e.g. `ou=users,dc=example,dc=com`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_dn: Option<String>,
+
+    /// Only members of these groups are allowed to connect, e.g. `hive-users`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_filter: Vec<String>,
+
+    /// The patterns of the group DNs, e.g. `cn=%s,ou=groups,dc=example,dc=com`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_dn_pattern: Vec<String>,
+
+    /// The attribute of a group listing its members, e.g. `member` or `uniqueMember`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_membership_key: Option<String>,
+
+    /// The object class of the groups, e.g. `groupOfNames`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_class_key: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Fragment, JsonSchema, PartialEq)]
+#[fragment_attrs(
+    derive(
+        Clone,
+        Debug,
+        Default,
+        Deserialize,
+        Merge,
+        JsonSchema,
+        PartialEq,
+        Serialize
+    ),
+    serde(rename_all = "camelCase")
+)]
+pub struct SecurityContextConfig {
+    /// Defines how the ownership and permissions of mounted volumes are changed to match the
+    /// `fsGroup` of the Pod. `OnRootMismatch` speeds up the start of Pods with large volumes, as the
+    /// ownership is only changed if the root of the volume does not match.
+    /// If not set, Kubernetes defaults to `Always`.
+    pub fs_group_change_policy: Option<FsGroupChangePolicy>,
+
+    /// Whether the root filesystem of the metastore container is mounted read-only.
+    /// All directories the metastore writes to, including `/tmp`, are backed by writable volumes.
+    /// Defaults to `false`.
+    pub read_only_root_filesystem: Option<bool>,
+
+    /// The user ID the metastore Pods run as. Must not be `0`, as the Pods are not allowed to run
+    /// as root. Defaults to `1000`.
+    pub run_as_user: Option<i64>,
+
+    /// The group ID the metastore Pods run as. Defaults to `0`, the group owning the files of the
+    /// image.
+    pub run_as_group: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, JsonSchema, PartialEq, Serialize)]
+pub enum FsGroupChangePolicy {
+    OnRootMismatch,
+    Always,
+}
+
+impl Atomic for FsGroupChangePolicy {}
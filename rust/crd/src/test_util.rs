@@ -0,0 +1,53 @@
+//! Fixtures shared by the unit tests of this crate and of the operator
+use crate::HiveCluster;
+
+/// A minimal [`HiveCluster`] without any role, which the tests extend with the `spec` fields they
+/// are interested in
+pub const HIVE_BASE: &str = r#"
+    apiVersion: hive.stackable.tech/v1alpha1
+    kind: HiveCluster
+    metadata:
+      name: simple-hive
+      namespace: default
+      uid: 6b1d4a76-5b3b-4a8e-9c39-4c8d3f9b8b2e
+    spec:
+      image:
+        productVersion: 4.0.0
+      clusterConfig:
+        database:
+          connString: jdbc:postgresql://postgresql:5432/hive
+          dbType: postgres
+          credentialsSecret: hive-credentials
+"#;
+
+/// Parses a [`HiveCluster`] consisting of [`HIVE_BASE`] extended by the given `spec` fields
+pub fn hive_from_yaml(spec: &str) -> HiveCluster {
+    serde_yaml::from_value(hive_yaml(spec)).expect("illegal test input")
+}
+
+/// The manifest of [`hive_from_yaml`], e.g. to test that it is rejected
+pub fn hive_yaml(spec: &str) -> serde_yaml::Value {
+    let mut hive: serde_yaml::Value =
+        serde_yaml::from_str(HIVE_BASE).expect("invalid base manifest");
+    let extra: serde_yaml::Value = serde_yaml::from_str(spec).expect("invalid spec");
+    merge_yaml(&mut hive["spec"], extra);
+    hive
+}
+
+/// Merges the mappings of `extra` recursively into `base`, all other values of `extra` replace the
+/// ones in `base`
+pub fn merge_yaml(base: &mut serde_yaml::Value, extra: serde_yaml::Value) {
+    match (base, extra) {
+        (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(extra)) => {
+            for (key, value) in extra {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, extra) => *base = extra,
+    }
+}
@@ -0,0 +1,320 @@
+//! Builds the [`HorizontalPodAutoscaler`] scaling the StatefulSet of a metastore role group
+use snafu::{OptionExt, ResultExt, Snafu};
+use stackable_hive_crd::{autoscaling::AutoscalingConfig, HiveCluster, HiveRole, APP_NAME};
+use stackable_operator::{
+    builder::meta::ObjectMetaBuilder,
+    client::Client,
+    commons::product_image_selection::ResolvedProductImage,
+    k8s_openapi::{
+        api::autoscaling::v2::{
+            CrossVersionObjectReference, HorizontalPodAutoscaler, HorizontalPodAutoscalerSpec,
+            MetricSpec, MetricTarget, ResourceMetricSource,
+        },
+        apimachinery::pkg::apis::meta::v1::LabelSelector,
+    },
+    kube::ResourceExt,
+    kvp::Labels,
+    role_utils::RoleGroupRef,
+};
+
+use crate::controller::{build_recommended_labels, HIVE_CONTROLLER_NAME};
+
+#[derive(Snafu, Debug)]
+pub enum Error {
+    #[snafu(display("object defines no namespace"))]
+    ObjectHasNoNamespace,
+
+    #[snafu(display("object is missing metadata to build owner reference"))]
+    ObjectMissingMetadataForOwnerRef {
+        source: stackable_operator::builder::meta::Error,
+    },
+
+    #[snafu(display("failed to build Labels"))]
+    LabelBuild {
+        source: stackable_operator::kvp::LabelError,
+    },
+
+    #[snafu(display("failed to build Metadata"))]
+    MetadataBuild {
+        source: stackable_operator::builder::meta::Error,
+    },
+
+    #[snafu(display("failed to apply HorizontalPodAutoscaler [{name}]"))]
+    ApplyHorizontalPodAutoscaler {
+        source: stackable_operator::client::Error,
+        name: String,
+    },
+
+    #[snafu(display("failed to list the HorizontalPodAutoscalers"))]
+    ListHorizontalPodAutoscalers {
+        source: stackable_operator::client::Error,
+    },
+
+    #[snafu(display("failed to delete HorizontalPodAutoscaler [{name}]"))]
+    DeleteHorizontalPodAutoscaler {
+        source: stackable_operator::client::Error,
+        name: String,
+    },
+}
+
+/// The [`HorizontalPodAutoscaler`] scales the StatefulSet of the role group between the configured
+/// bounds based on the average CPU and memory utilization of its Pods. Autoscaling is enabled by
+/// the `maxReplicas` of the role group, which is passed as `max_replicas`.
+pub fn build_horizontal_pod_autoscaler(
+    hive: &HiveCluster,
+    resolved_product_image: &ResolvedProductImage,
+    rolegroup_ref: &RoleGroupRef<HiveCluster>,
+    config: &AutoscalingConfig,
+    max_replicas: i32,
+) -> Result<HorizontalPodAutoscaler, Error> {
+    let metrics = [
+        ("cpu", config.target_cpu_utilization),
+        ("memory", config.target_memory_utilization),
+    ]
+    .into_iter()
+    .filter_map(|(resource, utilization)| {
+        utilization.map(|utilization| MetricSpec {
+            type_: "Resource".to_string(),
+            resource: Some(ResourceMetricSource {
+                name: resource.to_string(),
+                target: MetricTarget {
+                    type_: "Utilization".to_string(),
+                    average_utilization: Some(utilization),
+                    ..MetricTarget::default()
+                },
+            }),
+            ..MetricSpec::default()
+        })
+    })
+    .collect::<Vec<_>>();
+
+    Ok(HorizontalPodAutoscaler {
+        metadata: ObjectMetaBuilder::new()
+            .name_and_namespace(hive)
+            .name(rolegroup_ref.object_name())
+            .ownerreference_from_resource(hive, None, Some(true))
+            .context(ObjectMissingMetadataForOwnerRefSnafu)?
+            .with_recommended_labels(build_recommended_labels(
+                hive,
+                &resolved_product_image.app_version_label,
+                &rolegroup_ref.role,
+                &rolegroup_ref.role_group,
+            ))
+            .context(MetadataBuildSnafu)?
+            .build(),
+        spec: Some(HorizontalPodAutoscalerSpec {
+            scale_target_ref: CrossVersionObjectReference {
+                api_version: Some("apps/v1".to_string()),
+                kind: "StatefulSet".to_string(),
+                name: rolegroup_ref.object_name(),
+            },
+            min_replicas: Some(config.min_replicas()),
+            max_replicas,
+            // Kubernetes defaults to a CPU utilization of 80% if no metrics are given
+            metrics: (!metrics.is_empty()).then_some(metrics),
+            ..HorizontalPodAutoscalerSpec::default()
+        }),
+        ..HorizontalPodAutoscaler::default()
+    })
+}
+
+/// Creates or updates the [`HorizontalPodAutoscaler`]. It can not be managed by the
+/// `ClusterResources`, as `HorizontalPodAutoscaler` is no `ClusterResource`.
+pub async fn apply_horizontal_pod_autoscaler(
+    horizontal_pod_autoscaler: &HorizontalPodAutoscaler,
+    client: &Client,
+) -> Result<(), Error> {
+    client
+        .apply_patch(
+            HIVE_CONTROLLER_NAME,
+            horizontal_pod_autoscaler,
+            horizontal_pod_autoscaler,
+        )
+        .await
+        .with_context(|_| ApplyHorizontalPodAutoscalerSnafu {
+            name: horizontal_pod_autoscaler.name_any(),
+        })?;
+
+    Ok(())
+}
+
+/// Removes the [`HorizontalPodAutoscaler`]s of the HiveCluster which were not built in this
+/// reconciliation, i.e. of role groups with autoscaling disabled and of removed role groups
+pub async fn delete_orphaned_horizontal_pod_autoscalers(
+    hive: &HiveCluster,
+    horizontal_pod_autoscalers: &[HorizontalPodAutoscaler],
+    client: &Client,
+) -> Result<(), Error> {
+    let namespace = hive.namespace().context(ObjectHasNoNamespaceSnafu)?;
+    let selector = LabelSelector {
+        match_labels: Some(
+            Labels::role_selector(hive, APP_NAME, &HiveRole::MetaStore.to_string())
+                .context(LabelBuildSnafu)?
+                .into(),
+        ),
+        ..LabelSelector::default()
+    };
+
+    let existing = client
+        .list_with_label_selector::<HorizontalPodAutoscaler>(&namespace, &selector)
+        .await
+        .context(ListHorizontalPodAutoscalersSnafu)?;
+    for orphan in orphaned_horizontal_pod_autoscalers(existing, horizontal_pod_autoscalers) {
+        client
+            .delete(&orphan)
+            .await
+            .with_context(|_| DeleteHorizontalPodAutoscalerSnafu {
+                name: orphan.name_any(),
+            })?;
+    }
+
+    Ok(())
+}
+
+/// The `existing` [`HorizontalPodAutoscaler`]s which are not among the `built` ones
+fn orphaned_horizontal_pod_autoscalers(
+    existing: Vec<HorizontalPodAutoscaler>,
+    built: &[HorizontalPodAutoscaler],
+) -> Vec<HorizontalPodAutoscaler> {
+    existing
+        .into_iter()
+        .filter(|existing| {
+            !built
+                .iter()
+                .any(|built| built.name_any() == existing.name_any())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use stackable_hive_crd::test_util::hive_from_yaml;
+
+    use crate::controller::DOCKER_IMAGE_BASE_NAME;
+
+    fn build_rolegroup_horizontal_pod_autoscaler(
+        hive: &HiveCluster,
+        rolegroup_name: &str,
+    ) -> HorizontalPodAutoscaler {
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+        let rolegroup_ref = hive.metastore_rolegroup_ref(rolegroup_name);
+        let config = hive
+            .merged_config(&HiveRole::MetaStore, &rolegroup_ref)
+            .unwrap();
+
+        build_horizontal_pod_autoscaler(
+            hive,
+            &resolved_product_image,
+            &rolegroup_ref,
+            &config.autoscaling,
+            config.autoscaling.max_replicas.unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_horizontal_pod_autoscaler() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  config:
+                    autoscaling:
+                      minReplicas: 2
+                      maxReplicas: 5
+                      targetCpuUtilization: 75
+            "#,
+        );
+
+        let horizontal_pod_autoscaler = build_rolegroup_horizontal_pod_autoscaler(&hive, "default");
+        let spec = horizontal_pod_autoscaler.spec.unwrap();
+
+        assert_eq!(
+            horizontal_pod_autoscaler.metadata.name.as_deref(),
+            Some("simple-hive-metastore-default")
+        );
+        assert_eq!(
+            horizontal_pod_autoscaler
+                .metadata
+                .owner_references
+                .unwrap()
+                .first()
+                .map(|owner| owner.name.as_str()),
+            Some("simple-hive")
+        );
+        assert_eq!(
+            spec.scale_target_ref,
+            CrossVersionObjectReference {
+                api_version: Some("apps/v1".to_string()),
+                kind: "StatefulSet".to_string(),
+                name: "simple-hive-metastore-default".to_string(),
+            }
+        );
+        assert_eq!(spec.min_replicas, Some(2));
+        assert_eq!(spec.max_replicas, 5);
+        assert_eq!(
+            spec.metrics,
+            Some(vec![MetricSpec {
+                type_: "Resource".to_string(),
+                resource: Some(ResourceMetricSource {
+                    name: "cpu".to_string(),
+                    target: MetricTarget {
+                        type_: "Utilization".to_string(),
+                        average_utilization: Some(75),
+                        ..MetricTarget::default()
+                    },
+                }),
+                ..MetricSpec::default()
+            }])
+        );
+    }
+
+    #[test]
+    fn test_orphaned_horizontal_pod_autoscalers() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  config:
+                    autoscaling:
+                      maxReplicas: 5
+                removed:
+                  config:
+                    autoscaling:
+                      maxReplicas: 5
+                disabled:
+                  config:
+                    autoscaling:
+                      maxReplicas: 5
+            "#,
+        );
+        // The HorizontalPodAutoscalers created by previous reconciliations
+        let existing = ["default", "removed", "disabled"]
+            .into_iter()
+            .map(|rolegroup_name| build_rolegroup_horizontal_pod_autoscaler(&hive, rolegroup_name))
+            .collect::<Vec<_>>();
+        // Only the role group "default" still has autoscaling enabled
+        let built = [build_rolegroup_horizontal_pod_autoscaler(&hive, "default")];
+
+        let orphans = orphaned_horizontal_pod_autoscalers(existing, &built)
+            .iter()
+            .map(|orphan| orphan.name_any())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            orphans,
+            vec![
+                "simple-hive-metastore-removed".to_string(),
+                "simple-hive-metastore-disabled".to_string(),
+            ]
+        );
+        assert!(orphaned_horizontal_pod_autoscalers(vec![], &built).is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+//! Self-check of the cluster domain used for the FQDNs and Kerberos principals at startup
+use stackable_hive_crd::HiveCluster;
+use stackable_operator::{
+    client::Client,
+    kube::{api::ListParams, core::DeserializeGuard, ResourceExt},
+    namespace::WatchNamespace,
+    utils::cluster_info::KubernetesClusterInfoOpts,
+};
+
+/// The cluster domain the operator falls back to if none is configured
+const FALLBACK_CLUSTER_DOMAIN: &str = "cluster.local";
+
+/// Logs the cluster domain of the operator and warns if the fallback domain is used for
+/// HiveClusters with Kerberos, as their principals silently break on a wrong domain.
+pub async fn check_cluster_domain(
+    client: &Client,
+    watch_namespace: &WatchNamespace,
+    cluster_info_opts: &KubernetesClusterInfoOpts,
+) {
+    let cluster_domain = client.kubernetes_cluster_info.cluster_domain.to_string();
+    let explicitly_configured = cluster_info_opts.kubernetes_cluster_domain.is_some();
+    tracing::info!(
+        cluster_domain,
+        explicitly_configured,
+        "Using the cluster domain for FQDNs and Kerberos principals"
+    );
+
+    let hives = match watch_namespace
+        .get_api::<DeserializeGuard<HiveCluster>>(client)
+        .list(&ListParams::default())
+        .await
+    {
+        Ok(hives) => hives,
+        Err(error) => {
+            tracing::warn!(%error, "Failed to list the HiveClusters to check the cluster domain");
+            return;
+        }
+    };
+    let kerberos_clusters = hives
+        .iter()
+        .filter_map(|hive| hive.0.as_ref().ok())
+        .filter(|hive| uses_operator_cluster_domain_for_kerberos(hive))
+        .map(|hive| {
+            format!(
+                "{}/{}",
+                hive.namespace().unwrap_or_default(),
+                hive.name_any()
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(warning) =
+        cluster_domain_warning(&cluster_domain, explicitly_configured, &kerberos_clusters)
+    {
+        tracing::warn!("{warning}");
+    }
+}
+
+/// Whether the Kerberos principals of the [`HiveCluster`] are built with the cluster domain of
+/// the operator
+fn uses_operator_cluster_domain_for_kerberos(hive: &HiveCluster) -> bool {
+    hive.has_kerberos_enabled() && hive.spec.cluster_config.cluster_domain.is_none()
+}
+
+/// A warning if the operator falls back to the default cluster domain while HiveClusters with
+/// Kerberos depend on it
+fn cluster_domain_warning(
+    cluster_domain: &str,
+    explicitly_configured: bool,
+    kerberos_clusters: &[String],
+) -> Option<String> {
+    if explicitly_configured
+        || cluster_domain != FALLBACK_CLUSTER_DOMAIN
+        || kerberos_clusters.is_empty()
+    {
+        return None;
+    }
+
+    Some(format!(
+        "The operator falls back to the cluster domain {FALLBACK_CLUSTER_DOMAIN:?}, but the \
+        Kerberos principals of the HiveClusters [{clusters}] depend on it. If the cluster uses \
+        another domain, set KUBERNETES_CLUSTER_DOMAIN or clusterConfig.clusterDomain.",
+        clusters = kerberos_clusters.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::rstest;
+    use stackable_hive_crd::test_util::hive_from_yaml;
+
+    #[rstest]
+    #[case::fallback_with_kerberos("cluster.local", false, &["default/hive"], true)]
+    #[case::fallback_without_kerberos("cluster.local", false, &[], false)]
+    #[case::explicitly_configured("cluster.local", true, &["default/hive"], false)]
+    #[case::other_domain("example.internal", false, &["default/hive"], false)]
+    fn test_cluster_domain_warning(
+        #[case] cluster_domain: &str,
+        #[case] explicitly_configured: bool,
+        #[case] kerberos_clusters: &[&str],
+        #[case] warns: bool,
+    ) {
+        let kerberos_clusters = kerberos_clusters
+            .iter()
+            .map(|cluster| cluster.to_string())
+            .collect::<Vec<_>>();
+
+        let warning =
+            cluster_domain_warning(cluster_domain, explicitly_configured, &kerberos_clusters);
+
+        assert_eq!(warning.is_some(), warns);
+        if let Some(warning) = warning {
+            assert!(warning.contains("default/hive"));
+        }
+    }
+
+    #[rstest]
+    #[case::kerberos("{kerberos: {secretClass: kerberos}}", None, true)]
+    #[case::kerberos_with_cluster_domain(
+        "{kerberos: {secretClass: kerberos}}",
+        Some("example.internal"),
+        false
+    )]
+    #[case::no_kerberos("{}", None, false)]
+    fn test_uses_operator_cluster_domain_for_kerberos(
+        #[case] authentication: &str,
+        #[case] cluster_domain: Option<&str>,
+        #[case] expected: bool,
+    ) {
+        let mut hive = hive_from_yaml("metastore: {roleGroups: {default: {replicas: 1}}}");
+        hive.spec.cluster_config.authentication =
+            serde_yaml::from_str(authentication).expect("illegal test authentication");
+        hive.spec.cluster_config.cluster_domain = cluster_domain.map(str::to_string);
+
+        assert_eq!(uses_operator_cluster_domain_for_kerberos(&hive), expected);
+    }
+}
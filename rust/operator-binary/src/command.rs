@@ -1,18 +1,29 @@
+use indoc::formatdoc;
 use stackable_hive_crd::{
-    HiveCluster, DB_PASSWORD_ENV, DB_PASSWORD_PLACEHOLDER, DB_USERNAME_ENV,
-    DB_USERNAME_PLACEHOLDER, HIVE_METASTORE_LOG4J2_PROPERTIES, HIVE_SITE_XML, STACKABLE_CONFIG_DIR,
-    STACKABLE_CONFIG_MOUNT_DIR, STACKABLE_LOG_CONFIG_MOUNT_DIR, STACKABLE_TRUST_STORE,
-    STACKABLE_TRUST_STORE_PASSWORD, SYSTEM_TRUST_STORE, SYSTEM_TRUST_STORE_PASSWORD,
+    HiveCluster, DB_CREDENTIALS_DIR, DB_PASSWORD_ENV, DB_PASSWORD_PLACEHOLDER, DB_USERNAME_ENV,
+    DB_USERNAME_PLACEHOLDER, HIVE_METASTORE_LOG4J2_PROPERTIES, HIVE_SITE_XML,
+    JMX_REMOTE_ACCESS_FILE, JMX_REMOTE_PASSWORD_ENV, JMX_REMOTE_PASSWORD_FILE,
+    JMX_REMOTE_USERNAME_ENV, STACKABLE_CONFIG_DIR, STACKABLE_CONFIG_MOUNT_DIR,
+    STACKABLE_LOG_CONFIG_MOUNT_DIR, STACKABLE_TRUST_STORE, SYSTEM_TRUST_STORE,
+    SYSTEM_TRUST_STORE_PASSWORD, TRUST_STORE_PASSWORD_ENV,
 };
-use stackable_operator::commons::s3::S3ConnectionSpec;
+use stackable_operator::{commons::s3::S3ConnectionSpec, time::Duration};
+
+/// Stores the certificates of the truststore unencrypted, so that the JVM can read them without
+/// the truststore password, which is only passed to keytool
+const KEYTOOL_UNPROTECTED_CERTS_OPTS: &str =
+    "-J-Dkeystore.pkcs12.certProtectionAlgorithm=NONE -J-Dkeystore.pkcs12.macAlgorithm=NONE";
 
 pub fn build_container_command_args(
     hive: &HiveCluster,
     start_command: String,
     s3_connection_spec: Option<&S3ConnectionSpec>,
+    wait_for_database_timeout: Option<Duration>,
+    jmx_remote_enabled: bool,
 ) -> Vec<String> {
     let mut args = vec![
-        // copy config files to a writeable empty folder in order to set s3 access and secret keys
+        // copy config files, including the additional config files, to a writeable empty folder in
+        // order to set s3 access and secret keys
         format!("echo copying {STACKABLE_CONFIG_MOUNT_DIR} to {STACKABLE_CONFIG_DIR}"),
         format!("cp -RL {STACKABLE_CONFIG_MOUNT_DIR}/* {STACKABLE_CONFIG_DIR}"),
 
@@ -25,7 +36,7 @@ pub fn build_container_command_args(
         format!("if test -f {STACKABLE_CONFIG_DIR}/hive-site.xml; then config-utils template {STACKABLE_CONFIG_DIR}/hive-site.xml; fi"),
 
         // Copy system truststore to stackable truststore
-        format!("keytool -importkeystore -srckeystore {SYSTEM_TRUST_STORE} -srcstoretype jks -srcstorepass {SYSTEM_TRUST_STORE_PASSWORD} -destkeystore {STACKABLE_TRUST_STORE} -deststoretype pkcs12 -deststorepass {STACKABLE_TRUST_STORE_PASSWORD} -noprompt")
+        format!("keytool {KEYTOOL_UNPROTECTED_CERTS_OPTS} -importkeystore -srckeystore {SYSTEM_TRUST_STORE} -srcstoretype jks -srcstorepass {SYSTEM_TRUST_STORE_PASSWORD} -destkeystore {STACKABLE_TRUST_STORE} -deststoretype pkcs12 -deststorepass \"${TRUST_STORE_PASSWORD_ENV}\" -noprompt")
     ];
 
     if hive.spec.cluster_config.hdfs.is_some() {
@@ -38,19 +49,207 @@ pub fn build_container_command_args(
     if let Some(s3) = s3_connection_spec {
         if let Some(ca_cert) = s3.tls.tls_ca_cert_mount_path() {
             // The alias can not clash, as we only support a single S3Connection
-            args.push(format!("keytool -importcert -file {ca_cert} -alias stackable-s3-ca-cert -keystore {STACKABLE_TRUST_STORE} -storepass {STACKABLE_TRUST_STORE_PASSWORD} -noprompt"));
+            args.push(format!("keytool {KEYTOOL_UNPROTECTED_CERTS_OPTS} -importcert -file {ca_cert} -alias stackable-s3-ca-cert -keystore {STACKABLE_TRUST_STORE} -storepass \"${TRUST_STORE_PASSWORD_ENV}\" -noprompt"));
         }
     }
 
     // db credentials
+    if hive
+        .spec
+        .cluster_config
+        .database
+        .credentials_csi_volume
+        .is_some()
+    {
+        // The credentials are provided as files instead of environment variables from a Secret
+        args.extend([
+            format!("export {DB_USERNAME_ENV}=\"$(cat {DB_CREDENTIALS_DIR}/username)\""),
+            format!("export {DB_PASSWORD_ENV}=\"$(cat {DB_CREDENTIALS_DIR}/password)\""),
+        ]);
+    }
     args.extend([
         format!("echo replacing {DB_USERNAME_PLACEHOLDER} and {DB_PASSWORD_PLACEHOLDER} with secret values."),
         format!("sed -i \"s|{DB_USERNAME_PLACEHOLDER}|${DB_USERNAME_ENV}|g\" {STACKABLE_CONFIG_DIR}/{HIVE_SITE_XML}"),
         format!("sed -i \"s|{DB_PASSWORD_PLACEHOLDER}|${DB_PASSWORD_ENV}|g\" {STACKABLE_CONFIG_DIR}/{HIVE_SITE_XML}"),
     ]);
 
+    if jmx_remote_enabled {
+        args.push(jmx_remote_credentials_command());
+    }
+
+    if let Some(timeout) = wait_for_database_timeout {
+        match hive.spec.cluster_config.database.host_and_port() {
+            Some((host, port)) => args.push(wait_for_database_command(&host, port, timeout)),
+            None => tracing::warn!(
+                "Can not determine the database host from the connection string, not waiting for the database"
+            ),
+        }
+    }
+
     // metastore start command
     args.push(start_command);
 
     vec![args.join("\n")]
 }
+
+/// Forwards SIGTERM explicitly to the metastore process and its direct children. Depending on the
+/// shell and the `commandWrapper`, the JVM is a child of the started process and would otherwise
+/// not receive the signal, so that it is only killed after the graceful shutdown timeout.
+/// Expects the PID of the started process in `METASTORE_PID`, a signal received before is
+/// forwarded by `wait_for_termination`.
+pub const FORWARD_TERM_SIGNAL_COMMAND: &str = r#"trap 'kill -TERM "$METASTORE_PID" 2>/dev/null; pkill -TERM -P "$METASTORE_PID" 2>/dev/null' TERM"#;
+
+/// Creates the password and access files of the JMX remote port from the credentials Secret.
+/// The JVM refuses password files which are readable by other users. Tracing is disabled, so that
+/// the password does not end up in the logs.
+fn jmx_remote_credentials_command() -> String {
+    formatdoc! {"
+        echo \"Creating the JMX remote password and access files\"
+        (set +x; printf '%s %s\\n' \"${JMX_REMOTE_USERNAME_ENV}\" \"${JMX_REMOTE_PASSWORD_ENV}\" > {STACKABLE_CONFIG_DIR}/{JMX_REMOTE_PASSWORD_FILE})
+        chmod 600 {STACKABLE_CONFIG_DIR}/{JMX_REMOTE_PASSWORD_FILE}
+        (set +x; printf '%s readwrite\\n' \"${JMX_REMOTE_USERNAME_ENV}\" > {STACKABLE_CONFIG_DIR}/{JMX_REMOTE_ACCESS_FILE})"
+    }
+}
+
+/// Polls the database port until it accepts connections, failing the container after the `timeout`
+fn wait_for_database_command(host: &str, port: u16, timeout: Duration) -> String {
+    let timeout = timeout.as_secs();
+    formatdoc! {"
+        echo \"Waiting up to {timeout} seconds for the database at {host}:{port}\"
+        timeout {timeout} bash -c 'until (exec 3<>/dev/tcp/{host}/{port}) 2>/dev/null; do echo \"Database not reachable yet\"; sleep 2; done' || {{ echo \"Database at {host}:{port} was not reachable within {timeout} seconds\"; exit 1; }}"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::rstest;
+    use stackable_hive_crd::test_util::hive_from_yaml;
+
+    fn hive_with_database(db_type: &str, conn_string: &str) -> HiveCluster {
+        hive_from_yaml(&format!(
+            r#"
+            clusterConfig:
+              database:
+                connString: "{conn_string}"
+                dbType: {db_type}
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#
+        ))
+    }
+
+    #[rstest]
+    #[case::postgres(
+        "postgres",
+        "jdbc:postgresql://postgresql:5432/hive",
+        Some("2m"),
+        Some("timeout 120 bash -c 'until (exec 3<>/dev/tcp/postgresql/5432) 2>/dev/null;")
+    )]
+    #[case::default_port(
+        "postgres",
+        "jdbc:postgresql://postgresql/hive",
+        Some("2m"),
+        Some("timeout 120 bash -c 'until (exec 3<>/dev/tcp/postgresql/5432) 2>/dev/null;")
+    )]
+    #[case::mysql_with_parameters(
+        "mysql",
+        "jdbc:mysql://mysql:3307/hive?useSSL=false",
+        Some("1m30s"),
+        Some("timeout 90 bash -c 'until (exec 3<>/dev/tcp/mysql/3307) 2>/dev/null;")
+    )]
+    #[case::disabled("postgres", "jdbc:postgresql://postgresql:5432/hive", None, None)]
+    // Derby runs embedded, so there is nothing to wait for
+    #[case::derby(
+        "derby",
+        "jdbc:derby:;databaseName=/tmp/hive;create=true",
+        Some("2m"),
+        None
+    )]
+    #[case::unknown_host("postgres", "jdbc:postgresql:hive", Some("2m"), None)]
+    fn test_wait_for_database(
+        #[case] db_type: &str,
+        #[case] conn_string: &str,
+        #[case] timeout: Option<&str>,
+        #[case] expected: Option<&str>,
+    ) {
+        let hive = hive_with_database(db_type, conn_string);
+
+        let args = build_container_command_args(
+            &hive,
+            "start-metastore".to_string(),
+            None,
+            timeout.map(|timeout| timeout.parse().unwrap()),
+            false,
+        )
+        .join("\n");
+
+        match expected {
+            Some(wait_command) => {
+                assert!(args.contains(wait_command), "unexpected args {args}");
+                // The database has to be reachable before the metastore (and schematool) is started
+                assert!(args.find(wait_command) < args.find("start-metastore"));
+            }
+            None => assert!(!args.contains("/dev/tcp"), "unexpected args {args}"),
+        }
+    }
+
+    #[test]
+    fn test_hive_site_is_templated_before_start() {
+        let hive = hive_with_database("postgres", "jdbc:postgresql://postgresql:5432/hive");
+        let args =
+            build_container_command_args(&hive, "start-metastore".to_string(), None, None, false)
+                .join("\n");
+
+        // Resolves `${env:...}` placeholders, e.g. in the warehouse dir
+        let template_command =
+            format!("config-utils template {STACKABLE_CONFIG_DIR}/{HIVE_SITE_XML}");
+        assert!(args.contains(&template_command));
+        assert!(args.find(&template_command) < args.find("start-metastore"));
+    }
+
+    #[test]
+    fn test_db_credentials_from_csi_volume() {
+        let mut hive = hive_with_database("postgres", "jdbc:postgresql://postgresql:5432/hive");
+        hive.spec.cluster_config.database.credentials_secret = None;
+        hive.spec.cluster_config.database.credentials_csi_volume =
+            serde_yaml::from_str("volumeAttributes: {secretProviderClass: hive-db-credentials}")
+                .unwrap();
+
+        let args =
+            build_container_command_args(&hive, "start-metastore".to_string(), None, None, false)
+                .join("\n");
+
+        let export_command =
+            format!("export {DB_PASSWORD_ENV}=\"$(cat {DB_CREDENTIALS_DIR}/password)\"");
+        assert!(args.contains(&format!(
+            "export {DB_USERNAME_ENV}=\"$(cat {DB_CREDENTIALS_DIR}/username)\""
+        )));
+        assert!(args.contains(&export_command));
+        // The placeholders are replaced with the values read from the files
+        assert!(args.find(&export_command) < args.find(DB_PASSWORD_PLACEHOLDER));
+
+        let hive = hive_with_database("postgres", "jdbc:postgresql://postgresql:5432/hive");
+        let args =
+            build_container_command_args(&hive, "start-metastore".to_string(), None, None, false)
+                .join("\n");
+
+        assert!(!args.contains(DB_CREDENTIALS_DIR));
+    }
+
+    #[test]
+    fn test_jmx_remote_credentials() {
+        let hive = hive_with_database("postgres", "jdbc:postgresql://postgresql:5432/hive");
+        let args =
+            build_container_command_args(&hive, "start-metastore".to_string(), None, None, true)
+                .join("\n");
+
+        assert!(args.contains(&format!(
+            "chmod 600 {STACKABLE_CONFIG_DIR}/{JMX_REMOTE_PASSWORD_FILE}"
+        )));
+        assert!(args.find(JMX_REMOTE_ACCESS_FILE) < args.find("start-metastore"));
+    }
+}
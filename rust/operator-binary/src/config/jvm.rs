@@ -0,0 +1,81 @@
+//! Checks of the JVM arguments of the metastore container
+use stackable_operator::{
+    k8s_openapi::apimachinery::pkg::api::resource::Quantity,
+    memory::{BinaryMultiple, MemoryQuantity},
+};
+
+/// The maximum heap size in MiB set by the last `-Xmx` argument, as the JVM uses the last one
+fn max_heap_in_mebi(jvm_args: &str) -> Option<f32> {
+    let max_heap = jvm_args
+        .split_whitespace()
+        .rev()
+        .find_map(|arg| arg.strip_prefix("-Xmx"))?;
+
+    let (value, unit_in_mebi) = match max_heap.char_indices().next_back()? {
+        (index, 'k' | 'K') => (&max_heap[..index], 1.0 / 1024.0),
+        (index, 'm' | 'M') => (&max_heap[..index], 1.0),
+        (index, 'g' | 'G') => (&max_heap[..index], 1024.0),
+        (index, 't' | 'T') => (&max_heap[..index], 1024.0 * 1024.0),
+        _ => (max_heap, 1.0 / (1024.0 * 1024.0)),
+    };
+
+    value
+        .parse::<u64>()
+        .ok()
+        .map(|value| value as f32 * unit_in_mebi)
+}
+
+/// A warning if an `-Xmx` argument, e.g. added via the `envOverrides` of `HADOOP_OPTS`, exceeds
+/// the memory limit of the container, which gets the Pod OOM killed once the heap grows
+pub fn heap_size_warning(jvm_args: &str, memory_limit: &Quantity) -> Option<String> {
+    let max_heap = max_heap_in_mebi(jvm_args)?;
+    let memory_limit = MemoryQuantity::try_from(memory_limit)
+        .ok()?
+        .scale_to(BinaryMultiple::Mebi)
+        .value;
+
+    (max_heap > memory_limit).then(|| {
+        format!(
+            "The maximum heap size of {max_heap}Mi set via -Xmx exceeds the memory limit of \
+            {memory_limit}Mi, so the container will be OOM killed. Please increase the memory \
+            limit or decrease the heap size."
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("-Xmx4g", Some(4096.0))]
+    #[case("-Xms1g -Xmx2048m", Some(2048.0))]
+    #[case("-Xmx1g -Xmx512M", Some(512.0))]
+    #[case("-Xmx1048576k", Some(1024.0))]
+    #[case("-Xmx1073741824", Some(1024.0))]
+    #[case(
+        "-Djava.security.properties=/stackable/config/security.properties",
+        None
+    )]
+    #[case("-Xmxlarge", None)]
+    fn test_max_heap_in_mebi(#[case] jvm_args: &str, #[case] expected: Option<f32>) {
+        assert_eq!(max_heap_in_mebi(jvm_args), expected);
+    }
+
+    #[rstest]
+    #[case("-Xmx4g", "2Gi", true)]
+    #[case("-Xmx1g", "2Gi", false)]
+    #[case("-Xmx2g", "2Gi", false)]
+    #[case("-Dfoo=bar", "512Mi", false)]
+    fn test_heap_size_warning(
+        #[case] jvm_args: &str,
+        #[case] memory_limit: &str,
+        #[case] warns: bool,
+    ) {
+        let warning = heap_size_warning(jvm_args, &Quantity(memory_limit.to_string()));
+
+        assert_eq!(warning.is_some(), warns);
+    }
+}
@@ -0,0 +1,146 @@
+//! Detects `configOverrides` of properties which are managed by the operator
+use std::collections::BTreeSet;
+
+use stackable_hive_crd::{HiveCluster, MetaStoreConfig, HIVE_SITE_XML};
+use stackable_operator::status::condition::{
+    ClusterCondition, ClusterConditionSet, ClusterConditionStatus, ClusterConditionType,
+    ConditionBuilder,
+};
+
+/// Properties of the `hive-site.xml` the operator relies on, e.g. because they contain
+/// placeholders for credentials which are replaced in the container.
+const PROTECTED_HIVE_SITE_PROPERTIES: &[&str] = &[
+    MetaStoreConfig::CONNECTION_URL,
+    MetaStoreConfig::CONNECTION_DRIVER_NAME,
+    MetaStoreConfig::CONNECTION_USER_NAME,
+    MetaStoreConfig::CONNECTION_PASSWORD,
+    MetaStoreConfig::METASTORE_METRICS_ENABLED,
+];
+
+/// Returns the protected properties which are overridden on role or role group level
+pub fn protected_config_overrides(hive: &HiveCluster) -> BTreeSet<String> {
+    let Some(role) = &hive.spec.metastore else {
+        return BTreeSet::new();
+    };
+
+    std::iter::once(&role.config.config_overrides)
+        .chain(
+            role.role_groups
+                .values()
+                .map(|rolegroup| &rolegroup.config.config_overrides),
+        )
+        .filter_map(|config_overrides| config_overrides.get(HIVE_SITE_XML))
+        .flat_map(|overrides| overrides.keys())
+        .filter(|property| PROTECTED_HIVE_SITE_PROPERTIES.contains(&property.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Marks the cluster as degraded as long as protected properties are overridden
+pub struct ConfigOverridesConditionBuilder {
+    protected_overrides: BTreeSet<String>,
+}
+
+impl ConfigOverridesConditionBuilder {
+    pub fn new(protected_overrides: BTreeSet<String>) -> Self {
+        Self {
+            protected_overrides,
+        }
+    }
+}
+
+impl ConditionBuilder for ConfigOverridesConditionBuilder {
+    fn build_conditions(&self) -> ClusterConditionSet {
+        let (status, message) = if self.protected_overrides.is_empty() {
+            (
+                ClusterConditionStatus::False,
+                "No operator-managed properties are overridden".to_string(),
+            )
+        } else {
+            (
+                ClusterConditionStatus::True,
+                format!(
+                    "The operator-managed properties {properties:?} of the {HIVE_SITE_XML} are overridden, which can break the cluster",
+                    properties = self.protected_overrides
+                ),
+            )
+        };
+
+        vec![ClusterCondition {
+            reason: None,
+            message: Some(message),
+            status,
+            type_: ClusterConditionType::Degraded,
+            last_transition_time: None,
+            last_update_time: None,
+        }]
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use stackable_hive_crd::test_util::hive_from_yaml;
+
+    #[test]
+    fn test_protected_config_overrides() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              configOverrides:
+                hive-site.xml:
+                  javax.jdo.option.ConnectionPassword: secret
+                  datanucleus.connectionPool.maxPoolSize: "20"
+              roleGroups:
+                default:
+                  configOverrides:
+                    hive-site.xml:
+                      hive.metastore.metrics.enabled: "false"
+                  replicas: 1
+                secondary:
+                  configOverrides:
+                    # Overriding a property on both levels reports it once
+                    hive-site.xml:
+                      javax.jdo.option.ConnectionPassword: other-secret
+                    # Only the properties of the hive-site.xml are managed by the operator
+                    core-site.xml:
+                      javax.jdo.option.ConnectionURL: jdbc:postgresql://other:5432/hive
+                  replicas: 1
+            "#,
+        );
+
+        let protected_overrides = protected_config_overrides(&hive);
+        assert_eq!(
+            protected_overrides,
+            BTreeSet::from([
+                "hive.metastore.metrics.enabled".to_string(),
+                "javax.jdo.option.ConnectionPassword".to_string(),
+            ])
+        );
+
+        let conditions: Vec<ClusterCondition> =
+            ConfigOverridesConditionBuilder::new(protected_overrides)
+                .build_conditions()
+                .into();
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(conditions[0].type_, ClusterConditionType::Degraded);
+        assert_eq!(conditions[0].status, ClusterConditionStatus::True);
+        assert!(conditions[0]
+            .message
+            .as_ref()
+            .unwrap()
+            .contains("javax.jdo.option.ConnectionPassword"));
+    }
+
+    #[test]
+    fn test_no_protected_config_overrides() {
+        let conditions: Vec<ClusterCondition> =
+            ConfigOverridesConditionBuilder::new(BTreeSet::new())
+                .build_conditions()
+                .into();
+
+        assert_eq!(conditions[0].status, ClusterConditionStatus::False);
+    }
+}
@@ -1,7 +1,7 @@
 //! Ensures that `Pod`s are configured and running for each [`HiveCluster`]
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     hash::Hasher,
     sync::Arc,
 };
@@ -15,14 +15,21 @@ use product_config::{
 };
 use snafu::{OptionExt, ResultExt, Snafu};
 use stackable_hive_crd::{
-    Container, HiveCluster, HiveClusterStatus, HiveRole, MetaStoreConfig, APP_NAME, CORE_SITE_XML,
-    DB_PASSWORD_ENV, DB_USERNAME_ENV, HADOOP_HEAPSIZE, HIVE_ENV_SH, HIVE_PORT, HIVE_PORT_NAME,
-    HIVE_SITE_XML, JVM_HEAP_FACTOR, JVM_SECURITY_PROPERTIES_FILE, METRICS_PORT, METRICS_PORT_NAME,
-    STACKABLE_CONFIG_DIR, STACKABLE_CONFIG_DIR_NAME, STACKABLE_CONFIG_MOUNT_DIR,
+    autoscaling::AutoscalingConfig, Container, DbType, HiveCluster, HiveClusterStatus, HiveRole,
+    LogVolumeMedium, MetaStoreConfig, PodManagementPolicy, ReadinessCheck, RolegroupStatus,
+    UpdateStrategy, UpdateStrategyType, APP_NAME, CORE_SITE_XML, DB_CREDENTIALS_DIR,
+    DB_CREDENTIALS_DIR_NAME, DB_PASSWORD_ENV, DB_USERNAME_ENV, DEFAULT_ROLEGROUP_REPLICAS,
+    HADOOP_HEAPSIZE, HADOOP_OPTS, HDFS_SITE_XML, HIVE_ENV_SH, HIVE_PORT, HIVE_PORT_NAME,
+    HIVE_SITE_XML, JMX_REMOTE_PASSWORD_ENV, JMX_REMOTE_PORT, JMX_REMOTE_PORT_NAME,
+    JMX_REMOTE_USERNAME_ENV, JVM_ARGS_FILE, JVM_HEAP_FACTOR, JVM_SECURITY_PROPERTIES_FILE,
+    METRICS_PORT, METRICS_PORT_NAME, S3_ENDPOINT_ENV, S3_PROXY_PASSWORD_ENV, S3_PROXY_USERNAME_ENV,
+    S3_REGION_ENV, STACKABLE_CONFIG_DIR, STACKABLE_CONFIG_DIR_NAME, STACKABLE_CONFIG_MOUNT_DIR,
     STACKABLE_CONFIG_MOUNT_DIR_NAME, STACKABLE_LOG_CONFIG_MOUNT_DIR,
     STACKABLE_LOG_CONFIG_MOUNT_DIR_NAME, STACKABLE_LOG_DIR, STACKABLE_LOG_DIR_NAME,
+    STACKABLE_TRUST_STORE_PASSWORD, TMP_DIR, TMP_DIR_NAME, TRUST_STORE_PASSWORD_ENV,
 };
 
+use serde::Serialize;
 use stackable_operator::{
     builder::{
         self,
@@ -35,17 +42,21 @@ use stackable_operator::{
     },
     cluster_resources::{ClusterResourceApplyStrategy, ClusterResources},
     commons::{
-        product_image_selection::ResolvedProductImage,
-        rbac::build_rbac_resources,
-        s3::{S3AccessStyle, S3ConnectionSpec},
-        tls_verification::TlsClientDetailsError,
+        networking::DomainName, product_image_selection::ResolvedProductImage,
+        rbac::build_rbac_resources, s3::S3ConnectionSpec, tls_verification::TlsClientDetailsError,
     },
     k8s_openapi::{
         api::{
-            apps::v1::{StatefulSet, StatefulSetSpec},
+            apps::v1::{
+                RollingUpdateStatefulSetStrategy, StatefulSet,
+                StatefulSetPersistentVolumeClaimRetentionPolicy, StatefulSetSpec,
+                StatefulSetUpdateStrategy,
+            },
             core::v1::{
-                ConfigMap, ConfigMapVolumeSource, EmptyDirVolumeSource, Probe, Service,
-                ServicePort, ServiceSpec, TCPSocketAction, Volume,
+                CSIVolumeSource, ConfigMap, ConfigMapProjection, ConfigMapVolumeSource,
+                EmptyDirVolumeSource, ExecAction, KeyToPath, Lifecycle, LifecycleHandler, Probe,
+                ProjectedVolumeSource, ResourceRequirements, SecurityContext, Service, ServicePort,
+                ServiceSpec, TCPSocketAction, Volume, VolumeProjection,
             },
         },
         apimachinery::pkg::{
@@ -58,10 +69,13 @@ use stackable_operator::{
         runtime::controller::Action,
         Resource, ResourceExt,
     },
-    kvp::{Label, Labels, ObjectLabels},
+    kvp::{Annotations, Label, Labels, ObjectLabels},
     logging::controller::ReconcilerError,
     memory::{BinaryMultiple, MemoryQuantity},
-    product_config_utils::{transform_all_roles_to_config, validate_all_roles_and_groups_config},
+    product_config_utils::{
+        transform_all_roles_to_config, validate_all_roles_and_groups_config,
+        ValidatedRoleConfigByPropertyKind,
+    },
     product_logging::{
         self,
         framework::{
@@ -89,19 +103,35 @@ use tracing::warn;
 
 use crate::kerberos::{add_kerberos_pod_config, kerberos_config_properties};
 use crate::{
-    command::build_container_command_args,
-    discovery, kerberos,
+    autoscaling::{
+        self, apply_horizontal_pod_autoscaler, build_horizontal_pod_autoscaler,
+        delete_orphaned_horizontal_pod_autoscalers,
+    },
+    command::{build_container_command_args, FORWARD_TERM_SIGNAL_COMMAND},
+    config::jvm::heap_size_warning,
+    config_overrides::{protected_config_overrides, ConfigOverridesConditionBuilder},
+    discovery,
+    hdfs::warehouse_nameservice_warning,
+    kerberos,
     kerberos::kerberos_container_start_commands,
+    ldap::ldap_config_properties,
+    network_policy::{self, apply_network_policy, build_network_policy, delete_network_policy},
     operations::{graceful_shutdown::add_graceful_shutdown_config, pdb::add_pdbs},
     product_logging::{extend_role_group_config_map, resolve_vector_aggregator_address},
+    s3::{self, s3_config_properties},
+    service_monitor::{self, build_service_monitor, delete_service_monitor},
+    tls::{self, add_tls_pod_config, tls_config_properties},
+    zookeeper::zookeeper_discovery_config_properties,
     OPERATOR_NAME,
 };
 
 /// Used as runAsUser in the pod security context. This is specified in the kafka image file
 pub const HIVE_UID: i64 = 1000;
 pub const HIVE_CONTROLLER_NAME: &str = "hivecluster";
-const DOCKER_IMAGE_BASE_NAME: &str = "hive";
+const EPHEMERAL_STORAGE: &str = "ephemeral-storage";
+pub const DOCKER_IMAGE_BASE_NAME: &str = "hive";
 
+/// The default of the configurable maximum size of the Hive log files
 pub const MAX_HIVE_LOG_FILES_SIZE: MemoryQuantity = MemoryQuantity {
     value: 10.0,
     unit: BinaryMultiple::Mebi,
@@ -110,6 +140,10 @@ pub const MAX_HIVE_LOG_FILES_SIZE: MemoryQuantity = MemoryQuantity {
 pub struct Ctx {
     pub client: stackable_operator::client::Client,
     pub product_config: ProductConfigManager,
+    /// Builds all resources, but only logs them instead of applying them
+    pub dry_run: bool,
+    /// Interval in which HiveClusters are reconciled in addition to changes
+    pub reconcile_interval: Option<Duration>,
 }
 
 #[derive(Snafu, Debug, EnumDiscriminants)]
@@ -190,6 +224,9 @@ pub enum Error {
     #[snafu(display("failed to configure S3 connection"))]
     ConfigureS3 { source: S3Error },
 
+    #[snafu(display("failed to build the S3 properties"))]
+    BuildS3Properties { source: s3::Error },
+
     #[snafu(display("failed to configure S3 TLS client details"))]
     ConfigureS3TlsClientDetails { source: TlsClientDetailsError },
 
@@ -289,6 +326,22 @@ pub enum Error {
         source: stackable_operator::kvp::LabelError,
     },
 
+    #[snafu(display("invalid maximum size of the log files"))]
+    InvalidLogFilesSize {
+        source: stackable_operator::memory::Error,
+    },
+
+    #[snafu(display("invalid cluster domain {cluster_domain:?}"))]
+    InvalidClusterDomain {
+        source: stackable_operator::validation::Errors,
+        cluster_domain: String,
+    },
+
+    #[snafu(display("failed to build Annotations"))]
+    AnnotationBuild {
+        source: stackable_operator::kvp::AnnotationError,
+    },
+
     #[snafu(display("failed to build Metadata"))]
     MetadataBuild {
         source: stackable_operator::builder::meta::Error,
@@ -310,6 +363,9 @@ pub enum Error {
     #[snafu(display("failed to add kerberos config"))]
     AddKerberosConfig { source: kerberos::Error },
 
+    #[snafu(display("failed to add TLS config"))]
+    AddTlsConfig { source: tls::Error },
+
     #[snafu(display("failed to build vector container"))]
     BuildVectorContainer { source: LoggingError },
 
@@ -321,10 +377,85 @@ pub enum Error {
         source: builder::pod::container::Error,
     },
 
+    #[snafu(display("failed to build ServiceMonitor"))]
+    BuildServiceMonitor { source: service_monitor::Error },
+
+    #[snafu(display("failed to apply ServiceMonitor"))]
+    ApplyServiceMonitor {
+        source: stackable_operator::cluster_resources::Error,
+    },
+
+    #[snafu(display("failed to delete ServiceMonitor"))]
+    DeleteServiceMonitor { source: service_monitor::Error },
+
+    #[snafu(display("failed to build NetworkPolicy"))]
+    BuildNetworkPolicy { source: network_policy::Error },
+
+    #[snafu(display("failed to apply NetworkPolicy"))]
+    ApplyNetworkPolicy { source: network_policy::Error },
+
+    #[snafu(display("failed to delete NetworkPolicy"))]
+    DeleteNetworkPolicy { source: network_policy::Error },
+
+    #[snafu(display("failed to build HorizontalPodAutoscaler for {rolegroup}"))]
+    BuildHorizontalPodAutoscaler {
+        source: autoscaling::Error,
+        rolegroup: RoleGroupRef<HiveCluster>,
+    },
+
+    #[snafu(display("failed to apply HorizontalPodAutoscaler for {rolegroup}"))]
+    ApplyHorizontalPodAutoscaler {
+        source: autoscaling::Error,
+        rolegroup: RoleGroupRef<HiveCluster>,
+    },
+
+    #[snafu(display("failed to delete orphaned HorizontalPodAutoscalers"))]
+    DeleteOrphanedHorizontalPodAutoscalers { source: autoscaling::Error },
+
+    #[snafu(display(
+        "the embedded Derby database does not support multiple metastore replicas, so autoscaling can not be enabled for {rolegroup}. Please use a database such as PostgreSQL instead"
+    ))]
+    DerbyWithAutoscaling {
+        rolegroup: RoleGroupRef<HiveCluster>,
+    },
+
+    #[snafu(display(
+        "the autoscaling minReplicas ({min_replicas}) of {rolegroup} must not exceed its maxReplicas ({max_replicas})"
+    ))]
+    AutoscalingReplicasOutOfOrder {
+        rolegroup: RoleGroupRef<HiveCluster>,
+        min_replicas: i32,
+        max_replicas: i32,
+    },
+
     #[snafu(display("HiveCluster object is invalid"))]
     InvalidHiveCluster {
         source: error_boundary::InvalidObject,
     },
+
+    #[snafu(display(
+        "the embedded Derby database does not support multiple metastore replicas, but {replicas} replicas were requested. Please use a database such as PostgreSQL instead"
+    ))]
+    DerbyWithMultipleReplicas { replicas: u16 },
+
+    #[snafu(display(
+        "either credentialsSecret or credentialsCsiVolume has to be set for the database"
+    ))]
+    InvalidDatabaseCredentials,
+
+    #[snafu(display("failed to retrieve the HDFS discovery ConfigMap [{name}]"))]
+    RetrieveHdfsConfigMap {
+        source: stackable_operator::client::Error,
+        name: String,
+    },
+
+    #[snafu(display(
+        "the HDFS discovery ConfigMap [{name}] does not exist, please check clusterConfig.hdfs.configMap"
+    ))]
+    MissingHdfsConfigMap { name: String },
+
+    #[snafu(display("the HDFS discovery ConfigMap [{name}] does not contain the key [{key}]"))]
+    MissingHdfsConfigMapKey { name: String, key: String },
 }
 type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -347,6 +478,17 @@ pub async fn reconcile_hive(
     let client = &ctx.client;
     let hive_namespace = hive.namespace().context(ObjectHasNoNamespaceSnafu)?;
 
+    validate_replicas(hive)?;
+    let cluster_info = cluster_info(hive, &client.kubernetes_cluster_info)?;
+
+    let protected_overrides = protected_config_overrides(hive);
+    if !protected_overrides.is_empty() {
+        warn!(
+            ?protected_overrides,
+            "Overriding operator-managed properties can break the cluster or leak credentials"
+        );
+    }
+
     let resolved_product_image: ResolvedProductImage = hive
         .spec
         .image
@@ -368,31 +510,26 @@ pub async fn reconcile_hive(
             None
         };
 
-    let validated_config = validate_all_roles_and_groups_config(
+    let hdfs_site = if let Some(hdfs) = &hive.spec.cluster_config.hdfs {
+        let hdfs_config_map = client
+            .get_opt::<ConfigMap>(&hdfs.config_map, &hive_namespace)
+            .await
+            .with_context(|_| RetrieveHdfsConfigMapSnafu {
+                name: hdfs.config_map.clone(),
+            })?;
+        validate_hdfs_config_map(&hdfs.config_map, hdfs_config_map.as_ref())?;
+        hdfs_config_map
+            .and_then(|config_map| config_map.data)
+            .and_then(|mut data| data.remove(HDFS_SITE_XML))
+    } else {
+        None
+    };
+
+    let validated_config = validated_product_config(
+        hive,
         &resolved_product_image.product_version,
-        &transform_all_roles_to_config(
-            hive,
-            [(
-                HiveRole::MetaStore.to_string(),
-                (
-                    vec![
-                        PropertyNameKind::Env,
-                        PropertyNameKind::Cli,
-                        PropertyNameKind::File(HIVE_SITE_XML.to_string()),
-                        PropertyNameKind::File(HIVE_ENV_SH.to_string()),
-                        PropertyNameKind::File(JVM_SECURITY_PROPERTIES_FILE.to_string()),
-                    ],
-                    hive.spec.metastore.clone().context(NoMetaStoreRoleSnafu)?,
-                ),
-            )]
-            .into(),
-        )
-        .context(GenerateProductConfigSnafu)?,
         &ctx.product_config,
-        false,
-        false,
-    )
-    .context(InvalidProductConfigSnafu)?;
+    )?;
 
     let metastore_config = validated_config
         .get(&HiveRole::MetaStore.to_string())
@@ -404,7 +541,7 @@ pub async fn reconcile_hive(
         OPERATOR_NAME,
         HIVE_CONTROLLER_NAME,
         &hive.object_ref(&()),
-        ClusterResourceApplyStrategy::from(&hive.spec.cluster_operation),
+        cluster_resource_apply_strategy(hive, ctx.dry_run),
     )
     .context(CreateClusterResourcesSnafu)?;
 
@@ -417,10 +554,13 @@ pub async fn reconcile_hive(
     )
     .context(BuildRbacResourcesSnafu)?;
 
+    log_dry_run_resource(ctx.dry_run, &rbac_sa);
+
     let rbac_sa = cluster_resources
         .add(client, rbac_sa)
         .await
         .context(ApplyServiceAccountSnafu)?;
+    log_dry_run_resource(ctx.dry_run, &rbac_rolebinding);
     cluster_resources
         .add(client, rbac_rolebinding)
         .await
@@ -429,6 +569,7 @@ pub async fn reconcile_hive(
     let metastore_role_service = build_metastore_role_service(hive, &resolved_product_image)?;
 
     // we have to get the assigned ports
+    log_dry_run_resource(ctx.dry_run, &metastore_role_service);
     let metastore_role_service = cluster_resources
         .add(client, metastore_role_service)
         .await
@@ -439,6 +580,9 @@ pub async fn reconcile_hive(
         .context(ResolveVectorAggregatorAddressSnafu)?;
 
     let mut ss_cond_builder = StatefulSetConditionBuilder::default();
+    let out_of_band_operations = out_of_band_operations(hive, ctx.dry_run);
+    let mut rolegroup_statuses = BTreeMap::new();
+    let mut horizontal_pod_autoscalers = Vec::new();
 
     for (rolegroup_name, rolegroup_config) in metastore_config.iter() {
         let rolegroup = hive.metastore_rolegroup_ref(rolegroup_name);
@@ -447,22 +591,37 @@ pub async fn reconcile_hive(
             .merged_config(&HiveRole::MetaStore, &rolegroup)
             .context(FailedToResolveResourceConfigSnafu)?;
 
-        let rg_service = build_rolegroup_service(hive, &resolved_product_image, &rolegroup)?;
+        if let Some(hdfs_site) = &hdfs_site {
+            let (managed, external) = config.warehouse_dirs();
+            for warehouse_dir in managed.iter().chain(external.iter()) {
+                if let Some(warning) = warehouse_nameservice_warning(warehouse_dir, hdfs_site) {
+                    warn!(rolegroup = %rolegroup, "{warning}");
+                }
+            }
+        }
+
+        validate_autoscaling(hive, &rolegroup, &config.autoscaling)?;
+
+        let rolegroup_product_image = rolegroup_product_image(&config, &resolved_product_image);
+
+        let rg_service = build_rolegroup_service(hive, &rolegroup_product_image, &rolegroup)?;
+        let rg_metrics_service =
+            build_rolegroup_metrics_service(hive, &rolegroup_product_image, &rolegroup)?;
         let rg_configmap = build_metastore_rolegroup_config_map(
             hive,
             &hive_namespace,
-            &resolved_product_image,
+            &rolegroup_product_image,
             &rolegroup,
             rolegroup_config,
             s3_connection_spec.as_ref(),
             &config,
             vector_aggregator_address.as_deref(),
-            &client.kubernetes_cluster_info,
+            &cluster_info,
         )?;
         let rg_statefulset = build_metastore_rolegroup_statefulset(
             hive,
             &hive_role,
-            &resolved_product_image,
+            &rolegroup_product_image,
             &rolegroup,
             rolegroup_config,
             s3_connection_spec.as_ref(),
@@ -470,6 +629,8 @@ pub async fn reconcile_hive(
             &rbac_sa.name_any(),
         )?;
 
+        log_dry_run_resource(ctx.dry_run, &rg_service);
+
         cluster_resources
             .add(client, rg_service)
             .await
@@ -477,6 +638,19 @@ pub async fn reconcile_hive(
                 rolegroup: rolegroup.clone(),
             })?;
 
+        // A previously created metrics Service is removed as an orphan once it is disabled
+        if let Some(rg_metrics_service) = rg_metrics_service {
+            log_dry_run_resource(ctx.dry_run, &rg_metrics_service);
+            cluster_resources
+                .add(client, rg_metrics_service)
+                .await
+                .context(ApplyRoleGroupServiceSnafu {
+                    rolegroup: rolegroup.clone(),
+                })?;
+        }
+
+        log_dry_run_resource(ctx.dry_run, &rg_configmap);
+
         cluster_resources
             .add(client, rg_configmap)
             .await
@@ -484,14 +658,79 @@ pub async fn reconcile_hive(
                 rolegroup: rolegroup.clone(),
             })?;
 
-        ss_cond_builder.add(
-            cluster_resources
-                .add(client, rg_statefulset)
-                .await
-                .context(ApplyRoleGroupStatefulSetSnafu {
-                    rolegroup: rolegroup.clone(),
-                })?,
+        log_dry_run_resource(ctx.dry_run, &rg_statefulset);
+
+        let rg_statefulset = cluster_resources
+            .add(client, rg_statefulset)
+            .await
+            .context(ApplyRoleGroupStatefulSetSnafu {
+                rolegroup: rolegroup.clone(),
+            })?;
+        rolegroup_statuses.insert(
+            rolegroup_name.clone(),
+            build_rolegroup_status(&rg_statefulset),
         );
+        ss_cond_builder.add(rg_statefulset);
+
+        if let Some(max_replicas) = config.autoscaling.max_replicas {
+            let rg_horizontal_pod_autoscaler = build_horizontal_pod_autoscaler(
+                hive,
+                &rolegroup_product_image,
+                &rolegroup,
+                &config.autoscaling,
+                max_replicas,
+            )
+            .context(BuildHorizontalPodAutoscalerSnafu {
+                rolegroup: rolegroup.clone(),
+            })?;
+            log_dry_run_resource(ctx.dry_run, &rg_horizontal_pod_autoscaler);
+            if out_of_band_operations.contains(&OutOfBandOperation::ApplyHorizontalPodAutoscalers) {
+                apply_horizontal_pod_autoscaler(&rg_horizontal_pod_autoscaler, client)
+                    .await
+                    .context(ApplyHorizontalPodAutoscalerSnafu {
+                        rolegroup: rolegroup.clone(),
+                    })?;
+            }
+            horizontal_pod_autoscalers.push(rg_horizontal_pod_autoscaler);
+        }
+    }
+
+    // The HorizontalPodAutoscalers are no ClusterResources, so the ones of role groups which were
+    // removed or have autoscaling disabled are not deleted by delete_orphaned_resources
+    if out_of_band_operations.contains(&OutOfBandOperation::DeleteOrphanedHorizontalPodAutoscalers)
+    {
+        delete_orphaned_horizontal_pod_autoscalers(hive, &horizontal_pod_autoscalers, client)
+            .await
+            .context(DeleteOrphanedHorizontalPodAutoscalersSnafu)?;
+    }
+
+    if hive.spec.cluster_config.monitoring.service_monitor.enabled {
+        let service_monitor = build_service_monitor(hive, &resolved_product_image)
+            .context(BuildServiceMonitorSnafu)?;
+        log_dry_run_resource(ctx.dry_run, &service_monitor);
+        cluster_resources
+            .add(client, service_monitor)
+            .await
+            .context(ApplyServiceMonitorSnafu)?;
+    } else if out_of_band_operations.contains(&OutOfBandOperation::DeleteServiceMonitor) {
+        delete_service_monitor(hive, client)
+            .await
+            .context(DeleteServiceMonitorSnafu)?;
+    }
+
+    if hive.spec.cluster_config.network_policy.enabled {
+        let network_policy =
+            build_network_policy(hive, &resolved_product_image).context(BuildNetworkPolicySnafu)?;
+        log_dry_run_resource(ctx.dry_run, &network_policy);
+        if out_of_band_operations.contains(&OutOfBandOperation::ApplyNetworkPolicy) {
+            apply_network_policy(&network_policy, client)
+                .await
+                .context(ApplyNetworkPolicySnafu)?;
+        }
+    } else if out_of_band_operations.contains(&OutOfBandOperation::DeleteNetworkPolicy) {
+        delete_network_policy(hive, client)
+            .await
+            .context(DeleteNetworkPolicySnafu)?;
     }
 
     let role_config = hive.role_config(&hive_role);
@@ -514,10 +753,12 @@ pub async fn reconcile_hive(
         &resolved_product_image,
         &metastore_role_service,
         None,
+        &cluster_info,
     )
     .await
     .context(BuildDiscoveryConfigSnafu)?
     {
+        log_dry_run_resource(ctx.dry_run, &discovery_cm);
         let discovery_cm = cluster_resources
             .add(client, discovery_cm)
             .await
@@ -529,25 +770,81 @@ pub async fn reconcile_hive(
 
     let cluster_operation_cond_builder =
         ClusterOperationsConditionBuilder::new(&hive.spec.cluster_operation);
+    let config_overrides_cond_builder = ConfigOverridesConditionBuilder::new(protected_overrides);
 
     let status = HiveClusterStatus {
         // Serialize as a string to discourage users from trying to parse the value,
         // and to keep things flexible if we end up changing the hasher at some point.
         discovery_hash: Some(discovery_hash.finish().to_string()),
-        conditions: compute_conditions(hive, &[&ss_cond_builder, &cluster_operation_cond_builder]),
+        conditions: compute_conditions(
+            hive,
+            &[
+                &ss_cond_builder,
+                &cluster_operation_cond_builder,
+                &config_overrides_cond_builder,
+            ],
+        ),
+        rolegroups: rolegroup_statuses,
     };
 
-    client
-        .apply_patch_status(OPERATOR_NAME, hive, &status)
-        .await
-        .context(ApplyStatusSnafu)?;
+    if out_of_band_operations.contains(&OutOfBandOperation::PatchStatus) {
+        client
+            .apply_patch_status(OPERATOR_NAME, hive, &status)
+            .await
+            .context(ApplyStatusSnafu)?;
+    } else {
+        tracing::info!(?status, "Dry-run, not updating the status");
+    }
 
     cluster_resources
         .delete_orphaned_resources(client)
         .await
         .context(DeleteOrphanedResourcesSnafu)?;
 
-    Ok(Action::await_change())
+    Ok(reconcile_action(ctx.reconcile_interval))
+}
+
+/// The action after a successful reconciliation. Without a `reconcile_interval`, the HiveCluster is
+/// only reconciled again on changes, otherwise it is requeued to periodically correct drift.
+fn reconcile_action(reconcile_interval: Option<Duration>) -> Action {
+    match reconcile_interval {
+        Some(interval) => Action::requeue(*interval),
+        None => Action::await_change(),
+    }
+}
+
+/// Computes the configuration of all role groups and validates it against the product config
+fn validated_product_config(
+    hive: &HiveCluster,
+    product_version: &str,
+    product_config: &ProductConfigManager,
+) -> Result<ValidatedRoleConfigByPropertyKind> {
+    validate_all_roles_and_groups_config(
+        product_version,
+        &transform_all_roles_to_config(
+            hive,
+            [(
+                HiveRole::MetaStore.to_string(),
+                (
+                    vec![
+                        PropertyNameKind::Env,
+                        PropertyNameKind::Cli,
+                        PropertyNameKind::File(HIVE_SITE_XML.to_string()),
+                        PropertyNameKind::File(CORE_SITE_XML.to_string()),
+                        PropertyNameKind::File(HIVE_ENV_SH.to_string()),
+                        PropertyNameKind::File(JVM_SECURITY_PROPERTIES_FILE.to_string()),
+                    ],
+                    hive.spec.metastore.clone().context(NoMetaStoreRoleSnafu)?,
+                ),
+            )]
+            .into(),
+        )
+        .context(GenerateProductConfigSnafu)?,
+        product_config,
+        false,
+        false,
+    )
+    .context(InvalidProductConfigSnafu)
 }
 
 /// The server-role service is the primary endpoint that should be used by clients that do not
@@ -583,6 +880,19 @@ pub fn build_metastore_role_service(
                     .context(LabelBuildSnafu)?
                     .into(),
             ),
+            publish_not_ready_addresses: hive
+                .spec
+                .cluster_config
+                .services
+                .role
+                .publish_not_ready_addresses,
+            session_affinity: hive
+                .spec
+                .cluster_config
+                .services
+                .role
+                .session_affinity
+                .map(|session_affinity| session_affinity.to_string()),
             ..ServiceSpec::default()
         }),
         status: None,
@@ -604,6 +914,8 @@ fn build_metastore_rolegroup_config_map(
 ) -> Result<ConfigMap> {
     let mut hive_site_data = String::new();
     let mut hive_env_data = String::new();
+    let mut core_site_data = BTreeMap::new();
+    let mut hadoop_heapsize = None;
 
     for (property_name_kind, config) in role_group_config {
         match property_name_kind {
@@ -632,6 +944,7 @@ fn build_metastore_rolegroup_config_map(
                 for (property_name, property_value) in config {
                     data.insert(property_name.to_string(), Some(property_value.to_string()));
                 }
+                hadoop_heapsize = data.get(HADOOP_HEAPSIZE).cloned().flatten();
 
                 hive_env_data = data
                     .into_iter()
@@ -649,32 +962,17 @@ fn build_metastore_rolegroup_config_map(
                     Some("/stackable/warehouse".to_string()),
                 );
 
-                if let Some(s3) = s3_connection_spec {
-                    data.insert(
-                        MetaStoreConfig::S3_ENDPOINT.to_string(),
-                        Some(s3.endpoint().context(ConfigureS3Snafu)?.to_string()),
-                    );
-
-                    if let Some((access_key_file, secret_key_file)) = s3.credentials_mount_paths() {
-                        // Will be replaced by config-utils
-                        data.insert(
-                            MetaStoreConfig::S3_ACCESS_KEY.to_string(),
-                            Some(format!("${{file:UTF-8:{access_key_file}}}")),
-                        );
-                        data.insert(
-                            MetaStoreConfig::S3_SECRET_KEY.to_string(),
-                            Some(format!("${{file:UTF-8:{secret_key_file}}}")),
-                        );
-                    }
+                for (property_name, property_value) in
+                    merged_config.derived_hive_site_properties(hive)
+                {
+                    data.insert(property_name, Some(property_value));
+                }
 
-                    data.insert(
-                        MetaStoreConfig::S3_SSL_ENABLED.to_string(),
-                        Some(s3.tls.uses_tls().to_string()),
-                    );
-                    data.insert(
-                        MetaStoreConfig::S3_PATH_STYLE_ACCESS.to_string(),
-                        Some((s3.access_style == S3AccessStyle::Path).to_string()),
-                    );
+                for (property_name, property_value) in
+                    s3_config_properties(hive, s3_connection_spec)
+                        .context(BuildS3PropertiesSnafu)?
+                {
+                    data.insert(property_name, Some(property_value));
                 }
 
                 for (property_name, property_value) in
@@ -683,6 +981,18 @@ fn build_metastore_rolegroup_config_map(
                     data.insert(property_name.to_string(), Some(property_value.to_string()));
                 }
 
+                for (property_name, property_value) in tls_config_properties(hive) {
+                    data.insert(property_name, Some(property_value));
+                }
+
+                for (property_name, property_value) in ldap_config_properties(hive) {
+                    data.insert(property_name, Some(property_value));
+                }
+
+                for (property_name, property_value) in zookeeper_discovery_config_properties(hive) {
+                    data.insert(property_name, Some(property_value));
+                }
+
                 // overrides
                 for (property_name, property_value) in config {
                     data.insert(property_name.to_string(), Some(property_value.to_string()));
@@ -690,6 +1000,12 @@ fn build_metastore_rolegroup_config_map(
 
                 hive_site_data = to_hadoop_xml(data.iter());
             }
+            PropertyNameKind::File(file_name) if file_name == CORE_SITE_XML => {
+                for (property_name, property_value) in config {
+                    core_site_data
+                        .insert(property_name.to_string(), Some(property_value.to_string()));
+                }
+            }
             _ => {}
         }
     }
@@ -733,21 +1049,40 @@ fn build_metastore_rolegroup_config_map(
             })?,
         );
 
-    if hive.has_kerberos_enabled() && hive.spec.cluster_config.hdfs.is_none() {
+    let generate_core_site = hive
+        .spec
+        .cluster_config
+        .authentication
+        .as_ref()
+        .and_then(|authentication| authentication.kerberos.as_ref())
+        .is_some_and(|kerberos| kerberos.generate_core_site);
+    if generate_core_site && hive.spec.cluster_config.hdfs.is_none() {
         // if kerberos is activated but we have no HDFS as backend (i.e. S3) then a core-site.xml is
         // needed to set "hadoop.security.authentication"
-        let mut data = BTreeMap::new();
-        data.insert(
-            "hadoop.security.authentication".to_string(),
-            Some("kerberos".to_string()),
+        core_site_data
+            .entry("hadoop.security.authentication".to_string())
+            .or_insert(Some("kerberos".to_string()));
+    }
+
+    if !core_site_data.is_empty() {
+        cm_builder.add_data(CORE_SITE_XML, to_hadoop_xml(core_site_data.iter()));
+    }
+
+    if merged_config.render_jvm_args.unwrap_or(false) {
+        let hadoop_opts = role_group_config
+            .get(&PropertyNameKind::Env)
+            .and_then(|env| env.get(HADOOP_OPTS));
+        cm_builder.add_data(
+            JVM_ARGS_FILE,
+            jvm_args(hadoop_heapsize.as_deref(), hadoop_opts.map(String::as_str)),
         );
-        cm_builder.add_data(CORE_SITE_XML, to_hadoop_xml(data.iter()));
     }
 
     extend_role_group_config_map(
         rolegroup,
         vector_aggregator_address,
         &merged_config.logging,
+        max_log_files_size(merged_config)?,
         &mut cm_builder,
     )
     .context(InvalidLoggingConfigSnafu {
@@ -761,6 +1096,22 @@ fn build_metastore_rolegroup_config_map(
         })
 }
 
+/// The JVM arguments of the metastore, one per line. The Hadoop scripts turn the
+/// `HADOOP_HEAPSIZE` in MiB into the maximum heap size, which precedes the `HADOOP_OPTS`.
+fn jvm_args(hadoop_heapsize: Option<&str>, hadoop_opts: Option<&str>) -> String {
+    hadoop_heapsize
+        .map(|heapsize| format!("-Xmx{heapsize}m"))
+        .into_iter()
+        .chain(
+            hadoop_opts
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(str::to_string),
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// The rolegroup [`Service`] is a headless service that allows direct access to the instances of a certain rolegroup
 ///
 /// This is mostly useful for internal communication between peers, or for clients that perform client-side load balancing.
@@ -794,13 +1145,70 @@ fn build_rolegroup_service(
                     .context(LabelBuildSnafu)?
                     .into(),
             ),
-            publish_not_ready_addresses: Some(true),
+            publish_not_ready_addresses: Some(
+                hive.spec
+                    .cluster_config
+                    .services
+                    .role_group
+                    .publish_not_ready_addresses
+                    .unwrap_or(true),
+            ),
             ..ServiceSpec::default()
         }),
         status: None,
     })
 }
 
+/// The rolegroup metrics [`Service`] is a headless service exposing only the metrics port of the
+/// instances of a certain rolegroup.
+///
+/// It is not labeled with `prometheus.io/scrape`, as Prometheus already scrapes the metrics port
+/// of the rolegroup [`Service`] (from [`build_rolegroup_service`]).
+///
+/// Returns `None` if the metrics Service is disabled.
+fn build_rolegroup_metrics_service(
+    hive: &HiveCluster,
+    resolved_product_image: &ResolvedProductImage,
+    rolegroup: &RoleGroupRef<HiveCluster>,
+) -> Result<Option<Service>> {
+    if !hive.spec.cluster_config.monitoring.metrics_service.enabled {
+        return Ok(None);
+    }
+
+    Ok(Some(Service {
+        metadata: ObjectMetaBuilder::new()
+            .name_and_namespace(hive)
+            .name(rolegroup_metrics_service_name(rolegroup))
+            .ownerreference_from_resource(hive, None, Some(true))
+            .context(ObjectMissingMetadataForOwnerRefSnafu)?
+            .with_recommended_labels(build_recommended_labels(
+                hive,
+                &resolved_product_image.app_version_label,
+                &rolegroup.role,
+                &rolegroup.role_group,
+            ))
+            .context(MetadataBuildSnafu)?
+            .build(),
+        spec: Some(ServiceSpec {
+            type_: Some("ClusterIP".to_string()),
+            cluster_ip: Some("None".to_string()),
+            ports: Some(vec![metrics_service_port()]),
+            selector: Some(
+                Labels::role_group_selector(hive, APP_NAME, &rolegroup.role, &rolegroup.role_group)
+                    .context(LabelBuildSnafu)?
+                    .into(),
+            ),
+            publish_not_ready_addresses: Some(true),
+            ..ServiceSpec::default()
+        }),
+        status: None,
+    }))
+}
+
+fn rolegroup_metrics_service_name(rolegroup: &RoleGroupRef<HiveCluster>) -> String {
+    format!("{name}-metrics", name = rolegroup.object_name())
+}
+
 /// The rolegroup [`StatefulSet`] runs the rolegroup, as configured by the administrator.
 ///
 /// The [`Pod`](`stackable_operator::k8s_openapi::api::core::v1::Pod`)s are accessible through the
@@ -826,6 +1234,10 @@ fn build_metastore_rolegroup_statefulset(
             name: APP_NAME.to_string(),
         })?;
 
+    // The truststore password is only used by the keytool calls building the truststore and is
+    // not passed to the JVM
+    container_builder.add_env_vars(vec![trust_store_password_env_var(hive)]);
+
     for (property_name_kind, config) in metastore_config {
         if property_name_kind == &PropertyNameKind::Env {
             // overrides
@@ -838,6 +1250,13 @@ fn build_metastore_rolegroup_statefulset(
                     );
                     continue;
                 }
+                if property_name == HADOOP_OPTS {
+                    if let Some(memory_limit) = &merged_config.resources.memory.limit {
+                        if let Some(warning) = heap_size_warning(property_value, memory_limit) {
+                            warn!(rolegroup = %rolegroup_ref, "{warning}");
+                        }
+                    }
+                }
                 container_builder.add_env_var(property_name, property_value);
             }
         }
@@ -845,11 +1264,23 @@ fn build_metastore_rolegroup_statefulset(
 
     // load database credentials to environment variables: these will be used to replace
     // the placeholders in hive-site.xml so that the operator does not "touch" the secret.
-    let credentials_secret_name = hive.spec.cluster_config.database.credentials_secret.clone();
+    let database = &hive.spec.cluster_config.database;
+    match (
+        &database.credentials_secret,
+        &database.credentials_csi_volume,
+    ) {
+        (Some(credentials_secret_name), None) => {
+            container_builder.add_env_vars(vec![
+                env_var_from_secret(DB_USERNAME_ENV, credentials_secret_name, "username"),
+                env_var_from_secret(DB_PASSWORD_ENV, credentials_secret_name, "password"),
+            ]);
+        }
+        // The credentials are read from the mounted CSI volume in the start command instead
+        (None, Some(_)) => {}
+        _ => InvalidDatabaseCredentialsSnafu.fail()?,
+    }
 
     container_builder.add_env_vars(vec![
-        env_var_from_secret(DB_USERNAME_ENV, &credentials_secret_name, "username"),
-        env_var_from_secret(DB_PASSWORD_ENV, &credentials_secret_name, "password"),
         // Needed for the `containerdebug` process to log it's tracing information to.
         EnvVar {
             name: "CONTAINERDEBUG_LOG_DIRECTORY".to_string(),
@@ -858,8 +1289,73 @@ fn build_metastore_rolegroup_statefulset(
         },
     ]);
 
+    if let Some(proxy_credentials_secret) = hive
+        .spec
+        .cluster_config
+        .s3_tuning
+        .proxy
+        .as_ref()
+        .and_then(|proxy| proxy.credentials_secret.as_ref())
+    {
+        container_builder.add_env_vars(vec![
+            env_var_from_secret(S3_PROXY_USERNAME_ENV, proxy_credentials_secret, "username"),
+            env_var_from_secret(S3_PROXY_PASSWORD_ENV, proxy_credentials_secret, "password"),
+        ]);
+    }
+
+    if let Some(endpoint_secret) = &hive.spec.cluster_config.s3_tuning.endpoint_secret {
+        container_builder.add_env_vars(vec![
+            env_var_from_secret(S3_ENDPOINT_ENV, endpoint_secret, "endpoint"),
+            env_var_from_secret(S3_REGION_ENV, endpoint_secret, "region"),
+        ]);
+    }
+
+    if let Some(jmx_remote_secret) = &merged_config.jmx_remote_credentials_secret {
+        container_builder
+            .add_env_vars(vec![
+                env_var_from_secret(JMX_REMOTE_USERNAME_ENV, jmx_remote_secret, "username"),
+                env_var_from_secret(JMX_REMOTE_PASSWORD_ENV, jmx_remote_secret, "password"),
+            ])
+            .add_container_port(JMX_REMOTE_PORT_NAME, JMX_REMOTE_PORT.into());
+    }
+
     let mut pod_builder = PodBuilder::new();
 
+    let mut security_context = PodSecurityContextBuilder::new()
+        .run_as_user(
+            merged_config
+                .security_context
+                .run_as_user
+                .unwrap_or(HIVE_UID),
+        )
+        .run_as_group(merged_config.security_context.run_as_group.unwrap_or(0))
+        .fs_group(1000)
+        .build();
+    // Required by the `restricted` Pod Security Standard
+    security_context.run_as_non_root = Some(true);
+    security_context.fs_group_change_policy = merged_config
+        .security_context
+        .fs_group_change_policy
+        .map(|policy| policy.to_string());
+
+    if let Some(csi_volume) = &hive.spec.cluster_config.database.credentials_csi_volume {
+        pod_builder
+            .add_volume(Volume {
+                name: DB_CREDENTIALS_DIR_NAME.to_string(),
+                csi: Some(CSIVolumeSource {
+                    driver: csi_volume.driver.clone(),
+                    read_only: Some(true),
+                    volume_attributes: Some(csi_volume.volume_attributes.clone()),
+                    ..CSIVolumeSource::default()
+                }),
+                ..Volume::default()
+            })
+            .context(AddVolumeSnafu)?;
+        container_builder
+            .add_volume_mount(DB_CREDENTIALS_DIR_NAME, DB_CREDENTIALS_DIR)
+            .context(AddVolumeMountSnafu)?;
+    }
+
     if let Some(hdfs) = &hive.spec.cluster_config.hdfs {
         pod_builder
             .add_volume(
@@ -882,25 +1378,17 @@ fn build_metastore_rolegroup_statefulset(
         }
     }
 
-    let db_type = hive.db_type();
-    let start_command = if resolved_product_image.product_version.starts_with("3.") {
-        // The schematool version in 3.1.x does *not* support the `-initOrUpgradeSchema` flag yet, so we can not use that.
-        // As we *only* support HMS 3.1.x (or newer) since SDP release 23.11, we can safely assume we are always coming
-        // from an existing 3.1.x installation. There is no need to upgrade the schema, we can just check if the schema
-        // is already there and create it if it isn't.
-        // The script `bin/start-metastore` is buggy (e.g. around version upgrades), but it's sufficient for that job :)
-        //
-        // TODO: Once we drop support for HMS 3.1.x we can remove this condition and very likely get rid of the
-        // "bin/start-metastore" script.
-        format!("bin/start-metastore --config {STACKABLE_CONFIG_DIR} --db-type {db_type} --hive-bin-dir bin &")
-    } else {
-        // schematool versions 4.0.x (and above) support the `-initOrUpgradeSchema`, which is exactly what we need :)
-        // Some docs for the schemaTool can be found here: https://cwiki.apache.org/confluence/pages/viewpage.action?pageId=34835119
-        formatdoc! {"
-            bin/base --config \"{STACKABLE_CONFIG_DIR}\" --service schemaTool -dbType \"{db_type}\" -initOrUpgradeSchema
-            bin/base --config \"{STACKABLE_CONFIG_DIR}\" --service metastore &
-        "}
-    };
+    for (volume_name, volume) in &merged_config.resources.storage.additional_volumes {
+        container_builder
+            .add_volume_mount(volume_name, &volume.mount_path)
+            .context(AddVolumeMountSnafu)?;
+    }
+
+    let start_command = metastore_start_command(
+        &resolved_product_image.product_version,
+        hive.db_type(),
+        merged_config.command_wrapper.as_deref(),
+    );
 
     let container_builder = container_builder
         .image_from_product_image(resolved_product_image)
@@ -921,7 +1409,9 @@ fn build_metastore_rolegroup_statefulset(
             prepare_signal_handlers
             containerdebug --output={STACKABLE_LOG_DIR}/containerdebug-state.json --loop &
             {start_command}
-            wait_for_termination $!
+            METASTORE_PID=$!
+            {FORWARD_TERM_SIGNAL_COMMAND}
+            wait_for_termination $METASTORE_PID
             {create_vector_shutdown_file_command}
             ",
                 kerberos_container_start_commands = kerberos_container_start_commands(hive),
@@ -931,6 +1421,8 @@ fn build_metastore_rolegroup_statefulset(
                     create_vector_shutdown_file_command(STACKABLE_LOG_DIR),
             },
             s3_connection,
+            merged_config.wait_for_database_timeout,
+            merged_config.jmx_remote_credentials_secret.is_some(),
         ))
         .add_volume_mount(STACKABLE_CONFIG_DIR_NAME, STACKABLE_CONFIG_DIR)
         .context(AddVolumeMountSnafu)?
@@ -945,17 +1437,12 @@ fn build_metastore_rolegroup_statefulset(
         .context(AddVolumeMountSnafu)?
         .add_container_port(HIVE_PORT_NAME, HIVE_PORT.into())
         .add_container_port(METRICS_PORT_NAME, METRICS_PORT.into())
-        .resources(merged_config.resources.clone().into())
-        .readiness_probe(Probe {
-            initial_delay_seconds: Some(10),
-            period_seconds: Some(10),
-            failure_threshold: Some(5),
-            tcp_socket: Some(TCPSocketAction {
-                port: IntOrString::String(HIVE_PORT_NAME.to_string()),
-                ..TCPSocketAction::default()
-            }),
-            ..Probe::default()
-        })
+        .resources(container_resources(merged_config))
+        .readiness_probe(readiness_probe(
+            &resolved_product_image.product_version,
+            hive.db_type(),
+            merged_config.readiness_check,
+        ))
         .liveness_probe(Probe {
             initial_delay_seconds: Some(30),
             period_seconds: Some(10),
@@ -984,6 +1471,10 @@ fn build_metastore_rolegroup_statefulset(
             &rolegroup_ref.role_group,
         ))
         .context(MetadataBuildSnafu)?
+        .with_annotations(
+            Annotations::try_from(merged_config.pod_annotations.clone())
+                .context(AnnotationBuildSnafu)?,
+        )
         .build();
 
     pod_builder
@@ -998,31 +1489,22 @@ fn build_metastore_rolegroup_statefulset(
             ..Volume::default()
         })
         .context(AddVolumeSnafu)?
-        .add_volume(stackable_operator::k8s_openapi::api::core::v1::Volume {
-            name: STACKABLE_CONFIG_MOUNT_DIR_NAME.to_string(),
-            config_map: Some(ConfigMapVolumeSource {
-                name: rolegroup_ref.object_name(),
-                ..Default::default()
+        .add_volume(config_mount_volume(hive, rolegroup_ref))
+        .context(AddVolumeSnafu)?
+        .add_volume(Volume {
+            name: STACKABLE_LOG_DIR_NAME.to_string(),
+            empty_dir: Some(EmptyDirVolumeSource {
+                medium: log_volume_medium(merged_config),
+                size_limit: Some(product_logging::framework::calculate_log_volume_size_limit(
+                    &[max_log_files_size(merged_config)?],
+                )),
             }),
-            ..Default::default()
+            ..Volume::default()
         })
         .context(AddVolumeSnafu)?
-        .add_empty_dir_volume(
-            STACKABLE_LOG_DIR_NAME,
-            Some(product_logging::framework::calculate_log_volume_size_limit(
-                &[MAX_HIVE_LOG_FILES_SIZE],
-            )),
-        )
-        .context(AddVolumeSnafu)?
         .affinity(&merged_config.affinity)
         .service_account_name(sa_name)
-        .security_context(
-            PodSecurityContextBuilder::new()
-                .run_as_user(HIVE_UID)
-                .run_as_group(0)
-                .fs_group(1000)
-                .build(),
-        );
+        .security_context(security_context);
 
     if let Some(ContainerLogConfig {
         choice:
@@ -1061,8 +1543,55 @@ fn build_metastore_rolegroup_statefulset(
             .context(AddKerberosConfigSnafu)?;
     }
 
+    if hive.has_tls_authentication_enabled() {
+        add_tls_pod_config(hive, container_builder, &mut pod_builder).context(AddTlsConfigSnafu)?;
+    }
+
+    let read_only_root_filesystem = merged_config
+        .security_context
+        .read_only_root_filesystem
+        .unwrap_or_default();
+    if read_only_root_filesystem {
+        // The config, log and truststore paths are volumes already, but e.g. the scratch
+        // directories of Hive and the JVM live in /tmp
+        container_builder
+            .add_volume_mount(TMP_DIR_NAME, TMP_DIR)
+            .context(AddVolumeMountSnafu)?;
+        pod_builder
+            .add_empty_dir_volume(TMP_DIR_NAME, None)
+            .context(AddVolumeSnafu)?;
+    }
+
     // this is the main container
-    pod_builder.add_container(container_builder.build());
+    let mut hive_container = container_builder.build();
+    hive_container.working_dir = merged_config.working_dir.clone();
+    hive_container.lifecycle = merged_config
+        .pre_stop_command
+        .as_ref()
+        .map(|pre_stop_command| Lifecycle {
+            pre_stop: Some(LifecycleHandler {
+                exec: Some(ExecAction {
+                    command: Some(vec![
+                        "/bin/bash".to_string(),
+                        "-c".to_string(),
+                        pre_stop_command.to_string(),
+                    ]),
+                }),
+                ..LifecycleHandler::default()
+            }),
+            ..Lifecycle::default()
+        });
+    hive_container.termination_message_policy = merged_config
+        .termination_message_policy
+        .map(|policy| policy.to_string());
+    hive_container.termination_message_path = merged_config.termination_message_path.clone();
+    if read_only_root_filesystem {
+        hive_container.security_context = Some(SecurityContext {
+            read_only_root_filesystem: Some(true),
+            ..SecurityContext::default()
+        });
+    }
+    pod_builder.add_container(hive_container);
 
     // N.B. the vector container should *follow* the hive container so that the hive one is the
     // default, is started first and can provide any dependencies that vector expects
@@ -1085,9 +1614,24 @@ fn build_metastore_rolegroup_statefulset(
     }
 
     let mut pod_template = pod_builder.build_template();
+    if let Some(pod_spec) = pod_template.spec.as_mut() {
+        pod_spec.automount_service_account_token = merged_config.automount_service_account_token;
+    }
     pod_template.merge_from(role.config.pod_overrides.clone());
     pod_template.merge_from(rolegroup.config.pod_overrides.clone());
 
+    let volume_claim_templates = merged_config
+        .resources
+        .storage
+        .additional_volumes
+        .iter()
+        .map(|(volume_name, volume)| {
+            volume
+                .pvc
+                .build_pvc(volume_name, Some(vec!["ReadWriteOnce"]))
+        })
+        .collect::<Vec<_>>();
+
     Ok(StatefulSet {
         metadata: ObjectMetaBuilder::new()
             .name_and_namespace(hive)
@@ -1103,8 +1647,17 @@ fn build_metastore_rolegroup_statefulset(
             .context(MetadataBuildSnafu)?
             .build(),
         spec: Some(StatefulSetSpec {
-            pod_management_policy: Some("Parallel".to_string()),
-            replicas: rolegroup.replicas.map(i32::from),
+            min_ready_seconds: merged_config.min_ready_seconds,
+            pod_management_policy: Some(
+                merged_config
+                    .pod_management_policy
+                    .unwrap_or(PodManagementPolicy::Parallel)
+                    .to_string(),
+            ),
+            // The HorizontalPodAutoscaler owns the replicas, so they must not be reset on every
+            // reconciliation
+            replicas: (!merged_config.autoscaling.enabled())
+                .then(|| i32::from(rolegroup.replicas.unwrap_or(DEFAULT_ROLEGROUP_REPLICAS))),
             selector: LabelSelector {
                 match_labels: Some(
                     Labels::role_group_selector(
@@ -1120,12 +1673,437 @@ fn build_metastore_rolegroup_statefulset(
             },
             service_name: rolegroup_ref.object_name(),
             template: pod_template,
+            volume_claim_templates: (!volume_claim_templates.is_empty())
+                .then_some(volume_claim_templates),
+            persistent_volume_claim_retention_policy: volume_claim_retention_policy(merged_config),
+            update_strategy: merged_config.update_strategy.as_ref().map(update_strategy),
             ..StatefulSetSpec::default()
         }),
         status: None,
     })
 }
 
+/// The HDFS discovery ConfigMap is mounted into the metastore Pods, which would otherwise be
+/// stuck in `ContainerCreating` if it is missing
+fn validate_hdfs_config_map(name: &str, config_map: Option<&ConfigMap>) -> Result<()> {
+    let config_map = config_map.context(MissingHdfsConfigMapSnafu { name })?;
+
+    for key in [CORE_SITE_XML, HDFS_SITE_XML] {
+        let contains_key = config_map
+            .data
+            .as_ref()
+            .is_some_and(|data| data.contains_key(key));
+        if !contains_key {
+            return MissingHdfsConfigMapKeySnafu { name, key }.fail();
+        }
+    }
+
+    Ok(())
+}
+
+/// The image of the role group, which falls back to the image of the [`HiveCluster`] if it is not
+/// overridden in the role or role group config
+fn rolegroup_product_image(
+    merged_config: &MetaStoreConfig,
+    resolved_product_image: &ResolvedProductImage,
+) -> ResolvedProductImage {
+    match &merged_config.image {
+        Some(image) => image
+            .0
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION),
+        None => resolved_product_image.clone(),
+    }
+}
+
+/// In dry-run mode, no resources are applied or deleted, regardless of the cluster operation
+fn cluster_resource_apply_strategy(
+    hive: &HiveCluster,
+    dry_run: bool,
+) -> ClusterResourceApplyStrategy {
+    if dry_run {
+        ClusterResourceApplyStrategy::NoApply
+    } else {
+        ClusterResourceApplyStrategy::from(&hive.spec.cluster_operation)
+    }
+}
+
+/// Changes to the cluster which are not managed by the `ClusterResources` and therefore not covered
+/// by the [`ClusterResourceApplyStrategy`]
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum OutOfBandOperation {
+    ApplyHorizontalPodAutoscalers,
+    DeleteOrphanedHorizontalPodAutoscalers,
+    ApplyNetworkPolicy,
+    DeleteNetworkPolicy,
+    DeleteServiceMonitor,
+    PatchStatus,
+}
+
+/// The [`OutOfBandOperation`]s to run in this reconciliation. In dry-run mode, there are none.
+fn out_of_band_operations(hive: &HiveCluster, dry_run: bool) -> BTreeSet<OutOfBandOperation> {
+    if dry_run {
+        return BTreeSet::new();
+    }
+
+    let network_policy = if hive.spec.cluster_config.network_policy.enabled {
+        OutOfBandOperation::ApplyNetworkPolicy
+    } else {
+        OutOfBandOperation::DeleteNetworkPolicy
+    };
+
+    let mut operations = BTreeSet::from([
+        OutOfBandOperation::ApplyHorizontalPodAutoscalers,
+        OutOfBandOperation::DeleteOrphanedHorizontalPodAutoscalers,
+        network_policy,
+        OutOfBandOperation::PatchStatus,
+    ]);
+    // An enabled ServiceMonitor is a ClusterResource
+    if !hive.spec.cluster_config.monitoring.service_monitor.enabled {
+        operations.insert(OutOfBandOperation::DeleteServiceMonitor);
+    }
+    operations
+}
+
+/// Logs the given resource as JSON in dry-run mode, as it is not applied
+fn log_dry_run_resource<T>(dry_run: bool, resource: &T)
+where
+    T: Resource<DynamicType = ()> + Serialize,
+{
+    if !dry_run {
+        return;
+    }
+
+    match serde_json::to_string(resource) {
+        Ok(manifest) => tracing::info!(
+            kind = %T::kind(&()),
+            name = %resource.name_any(),
+            %manifest,
+            "Dry-run, not applying resource"
+        ),
+        Err(error) => warn!(
+            kind = %T::kind(&()),
+            name = %resource.name_any(),
+            %error,
+            "Dry-run, failed to serialize resource"
+        ),
+    }
+}
+
+/// The cluster info used to build FQDNs, with the cluster domain of the [`HiveCluster`] taking
+/// precedence over the one of the operator.
+fn cluster_info(
+    hive: &HiveCluster,
+    operator_cluster_info: &KubernetesClusterInfo,
+) -> Result<KubernetesClusterInfo> {
+    let cluster_domain = match &hive.spec.cluster_config.cluster_domain {
+        Some(cluster_domain) => {
+            DomainName::try_from(cluster_domain.as_str()).with_context(|_| {
+                InvalidClusterDomainSnafu {
+                    cluster_domain: cluster_domain.clone(),
+                }
+            })?
+        }
+        None => operator_cluster_info.cluster_domain.clone(),
+    };
+
+    Ok(KubernetesClusterInfo { cluster_domain })
+}
+
+/// Kubernetes deletes the PersistentVolumeClaims of removed Pods according to this policy, so
+/// they do not linger after scaling down
+fn volume_claim_retention_policy(
+    merged_config: &MetaStoreConfig,
+) -> Option<StatefulSetPersistentVolumeClaimRetentionPolicy> {
+    let retention = &merged_config.resources.storage.volume_claim_retention;
+    if retention.when_scaled.is_none() && retention.when_deleted.is_none() {
+        return None;
+    }
+
+    Some(StatefulSetPersistentVolumeClaimRetentionPolicy {
+        when_scaled: retention.when_scaled.map(|policy| policy.to_string()),
+        when_deleted: retention.when_deleted.map(|policy| policy.to_string()),
+    })
+}
+
+fn update_strategy(update_strategy: &UpdateStrategy) -> StatefulSetUpdateStrategy {
+    let rolling_update = match update_strategy.strategy_type {
+        UpdateStrategyType::RollingUpdate => {
+            update_strategy
+                .partition
+                .map(|partition| RollingUpdateStatefulSetStrategy {
+                    partition: Some(partition),
+                    ..RollingUpdateStatefulSetStrategy::default()
+                })
+        }
+        UpdateStrategyType::OnDelete => None,
+    };
+
+    StatefulSetUpdateStrategy {
+        type_: Some(update_strategy.strategy_type.to_string()),
+        rolling_update,
+    }
+}
+
+/// The configured maximum size of the Hive log files or the default
+fn max_log_files_size(merged_config: &MetaStoreConfig) -> Result<MemoryQuantity> {
+    match &merged_config.log_volume.max_files_size {
+        Some(max_files_size) => {
+            MemoryQuantity::try_from(max_files_size).context(InvalidLogFilesSizeSnafu)
+        }
+        None => Ok(MAX_HIVE_LOG_FILES_SIZE),
+    }
+}
+
+/// The `emptyDir` medium of the log volume, `None` selects the default medium of the node
+fn log_volume_medium(merged_config: &MetaStoreConfig) -> Option<String> {
+    match merged_config.log_volume.medium {
+        Some(LogVolumeMedium::Memory) => Some("Memory".to_string()),
+        Some(LogVolumeMedium::Disk) | None => None,
+    }
+}
+
+/// Derby runs embedded in every metastore Pod, so multiple replicas would not share their state
+/// and lead to inconsistent metadata.
+fn validate_replicas(hive: &HiveCluster) -> Result<()> {
+    if *hive.db_type() != DbType::Derby {
+        return Ok(());
+    }
+
+    let replicas: u16 = hive
+        .spec
+        .metastore
+        .iter()
+        .flat_map(|role| role.role_groups.values())
+        .map(|rolegroup| rolegroup.replicas.unwrap_or(DEFAULT_ROLEGROUP_REPLICAS))
+        .sum();
+
+    if replicas > 1 {
+        return DerbyWithMultipleReplicasSnafu { replicas }.fail();
+    }
+
+    Ok(())
+}
+
+/// Rejects autoscaling with the embedded Derby database and replica bounds the
+/// HorizontalPodAutoscaler can not satisfy
+fn validate_autoscaling(
+    hive: &HiveCluster,
+    rolegroup: &RoleGroupRef<HiveCluster>,
+    autoscaling: &AutoscalingConfig,
+) -> Result<()> {
+    let Some(max_replicas) = autoscaling.max_replicas else {
+        return Ok(());
+    };
+
+    if *hive.db_type() == DbType::Derby {
+        return DerbyWithAutoscalingSnafu {
+            rolegroup: rolegroup.clone(),
+        }
+        .fail();
+    }
+
+    let min_replicas = autoscaling.min_replicas();
+    if min_replicas > max_replicas {
+        return AutoscalingReplicasOutOfOrderSnafu {
+            rolegroup: rolegroup.clone(),
+            min_replicas,
+            max_replicas,
+        }
+        .fail();
+    }
+
+    Ok(())
+}
+
+/// The volume mounted at the [`STACKABLE_CONFIG_MOUNT_DIR`], which contains the role group
+/// ConfigMap and the additional config files. Its content is copied to the config directory when
+/// the container starts.
+fn config_mount_volume(hive: &HiveCluster, rolegroup_ref: &RoleGroupRef<HiveCluster>) -> Volume {
+    match &hive.spec.cluster_config.additional_config_files {
+        Some(additional_config_files) => Volume {
+            name: STACKABLE_CONFIG_MOUNT_DIR_NAME.to_string(),
+            projected: Some(ProjectedVolumeSource {
+                sources: Some(vec![
+                    VolumeProjection {
+                        config_map: Some(ConfigMapProjection {
+                            name: rolegroup_ref.object_name(),
+                            ..ConfigMapProjection::default()
+                        }),
+                        ..VolumeProjection::default()
+                    },
+                    VolumeProjection {
+                        config_map: Some(ConfigMapProjection {
+                            name: additional_config_files.config_map.clone(),
+                            items: Some(
+                                additional_config_files
+                                    .files
+                                    .iter()
+                                    .map(|file| KeyToPath {
+                                        key: file.clone(),
+                                        path: file.clone(),
+                                        ..KeyToPath::default()
+                                    })
+                                    .collect(),
+                            ),
+                            ..ConfigMapProjection::default()
+                        }),
+                        ..VolumeProjection::default()
+                    },
+                ]),
+                ..ProjectedVolumeSource::default()
+            }),
+            ..Volume::default()
+        },
+        None => Volume {
+            name: STACKABLE_CONFIG_MOUNT_DIR_NAME.to_string(),
+            config_map: Some(ConfigMapVolumeSource {
+                name: rolegroup_ref.object_name(),
+                ..ConfigMapVolumeSource::default()
+            }),
+            ..Volume::default()
+        },
+    }
+}
+
+/// The [`ResourceRequirements`] of the Hive container, extended by the `ephemeral-storage`
+/// request and limit, which are not part of the generic resources.
+fn container_resources(merged_config: &MetaStoreConfig) -> ResourceRequirements {
+    let mut resources: ResourceRequirements = merged_config.resources.clone().into();
+    let ephemeral = &merged_config.resources.storage.ephemeral;
+
+    // The generic resources always request the memory limit
+    if let Some(memory_request) = &merged_config.memory_request {
+        resources
+            .requests
+            .get_or_insert_with(BTreeMap::new)
+            .insert("memory".to_string(), memory_request.clone());
+    }
+    if let Some(request) = &ephemeral.request {
+        resources
+            .requests
+            .get_or_insert_with(BTreeMap::new)
+            .insert(EPHEMERAL_STORAGE.to_string(), request.clone());
+    }
+    if let Some(limit) = &ephemeral.limit {
+        resources
+            .limits
+            .get_or_insert_with(BTreeMap::new)
+            .insert(EPHEMERAL_STORAGE.to_string(), limit.clone());
+    }
+
+    resources
+}
+
+/// The command starting the metastore in the background. The optional `command_wrapper` is put in
+/// front of the metastore process, e.g. to run it with a profiler.
+fn metastore_start_command(
+    product_version: &str,
+    db_type: &DbType,
+    command_wrapper: Option<&str>,
+) -> String {
+    let command_wrapper = command_wrapper
+        .map(|wrapper| format!("{wrapper} "))
+        .unwrap_or_default();
+
+    if product_version.starts_with("3.") {
+        // The schematool version in 3.1.x does *not* support the `-initOrUpgradeSchema` flag yet, so we can not use that.
+        // As we *only* support HMS 3.1.x (or newer) since SDP release 23.11, we can safely assume we are always coming
+        // from an existing 3.1.x installation. There is no need to upgrade the schema, we can just check if the schema
+        // is already there and create it if it isn't.
+        // The script `bin/start-metastore` is buggy (e.g. around version upgrades), but it's sufficient for that job :)
+        //
+        // TODO: Once we drop support for HMS 3.1.x we can remove this condition and very likely get rid of the
+        // "bin/start-metastore" script.
+        format!("{command_wrapper}bin/start-metastore --config {STACKABLE_CONFIG_DIR} --db-type {db_type} --hive-bin-dir bin &")
+    } else {
+        // schematool versions 4.0.x (and above) support the `-initOrUpgradeSchema`, which is exactly what we need :)
+        // Some docs for the schemaTool can be found here: https://cwiki.apache.org/confluence/pages/viewpage.action?pageId=34835119
+        formatdoc! {"
+            bin/base --config \"{STACKABLE_CONFIG_DIR}\" --service schemaTool -dbType \"{db_type}\" -initOrUpgradeSchema
+            {command_wrapper}bin/base --config \"{STACKABLE_CONFIG_DIR}\" --service metastore &
+        "}
+    }
+}
+
+/// The readiness probe of the metastore container, which either checks that the Thrift port
+/// accepts connections or that `schematool` can read the schema information from the database
+fn readiness_probe(
+    product_version: &str,
+    db_type: &DbType,
+    readiness_check: Option<ReadinessCheck>,
+) -> Probe {
+    match readiness_check.unwrap_or(ReadinessCheck::ThriftPort) {
+        ReadinessCheck::ThriftPort => Probe {
+            initial_delay_seconds: Some(10),
+            period_seconds: Some(10),
+            failure_threshold: Some(5),
+            tcp_socket: Some(TCPSocketAction {
+                port: IntOrString::String(HIVE_PORT_NAME.to_string()),
+                ..TCPSocketAction::default()
+            }),
+            ..Probe::default()
+        },
+        // schematool starts a JVM, so it is checked less often and with a longer timeout
+        ReadinessCheck::SchemaTool => Probe {
+            initial_delay_seconds: Some(10),
+            period_seconds: Some(30),
+            timeout_seconds: Some(30),
+            failure_threshold: Some(5),
+            exec: Some(ExecAction {
+                command: Some(vec![
+                    "/bin/bash".to_string(),
+                    "-c".to_string(),
+                    schema_info_command(product_version, db_type),
+                ]),
+            }),
+            ..Probe::default()
+        },
+    }
+}
+
+/// The `schematool` call printing the schema version, which fails if the database is not reachable
+/// or contains no metastore schema
+fn schema_info_command(product_version: &str, db_type: &DbType) -> String {
+    if product_version.starts_with("3.") {
+        // The schematool of 3.1.x reads the config from the METASTORE_CONF_DIR, as it does not
+        // support the `--config` flag of `bin/base`
+        format!("METASTORE_CONF_DIR={STACKABLE_CONFIG_DIR} bin/schematool -dbType {db_type} -info")
+    } else {
+        format!(
+            "bin/base --config \"{STACKABLE_CONFIG_DIR}\" --service schemaTool -dbType \"{db_type}\" -info"
+        )
+    }
+}
+
+/// Summarizes the readiness of a role group [`StatefulSet`] for the [`HiveClusterStatus`]
+fn build_rolegroup_status(statefulset: &StatefulSet) -> RolegroupStatus {
+    RolegroupStatus {
+        // Kubernetes defaults to a single replica if none is set
+        desired_replicas: statefulset
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.replicas)
+            .unwrap_or(1),
+        ready_replicas: statefulset
+            .status
+            .as_ref()
+            .and_then(|status| status.ready_replicas)
+            .unwrap_or_default(),
+    }
+}
+
+/// The truststore password is taken from the configured Secret or falls back to the default
+fn trust_store_password_env_var(hive: &HiveCluster) -> EnvVar {
+    match &hive.spec.cluster_config.trust_store_password_secret {
+        Some(secret) => env_var_from_secret(TRUST_STORE_PASSWORD_ENV, secret, "password"),
+        None => EnvVar {
+            name: TRUST_STORE_PASSWORD_ENV.to_string(),
+            value: Some(STACKABLE_TRUST_STORE_PASSWORD.to_string()),
+            value_from: None,
+        },
+    }
+}
+
 fn env_var_from_secret(var_name: &str, secret: &str, secret_key: &str) -> EnvVar {
     EnvVar {
         name: String::from(var_name),
@@ -1154,20 +2132,25 @@ pub fn error_policy(
 }
 
 pub fn service_ports() -> Vec<ServicePort> {
-    vec![
-        ServicePort {
-            name: Some(HIVE_PORT_NAME.to_string()),
-            port: HIVE_PORT.into(),
-            protocol: Some("TCP".to_string()),
-            ..ServicePort::default()
-        },
-        ServicePort {
-            name: Some(METRICS_PORT_NAME.to_string()),
-            port: METRICS_PORT.into(),
-            protocol: Some("TCP".to_string()),
-            ..ServicePort::default()
-        },
-    ]
+    vec![hive_service_port(), metrics_service_port()]
+}
+
+fn hive_service_port() -> ServicePort {
+    ServicePort {
+        name: Some(HIVE_PORT_NAME.to_string()),
+        port: HIVE_PORT.into(),
+        protocol: Some("TCP".to_string()),
+        ..ServicePort::default()
+    }
+}
+
+fn metrics_service_port() -> ServicePort {
+    ServicePort {
+        name: Some(METRICS_PORT_NAME.to_string()),
+        port: METRICS_PORT.into(),
+        protocol: Some("TCP".to_string()),
+        ..ServicePort::default()
+    }
 }
 
 /// Creates recommended `ObjectLabels` to be used in deployed resources
@@ -1187,3 +2170,2185 @@ pub fn build_recommended_labels<'a, T>(
         role_group,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::rstest;
+    use stackable_hive_crd::{
+        test_util::hive_from_yaml, HIVE_METASTORE_LOG4J2_PROPERTIES, STACKABLE_TRUST_STORE,
+    };
+    use stackable_operator::k8s_openapi::api::apps::v1::StatefulSetStatus;
+
+    fn build_statefulset(hive: &HiveCluster, rolegroup_name: &str) -> StatefulSet {
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+        let rolegroup_ref = hive.metastore_rolegroup_ref(rolegroup_name);
+        let merged_config = hive
+            .merged_config(&HiveRole::MetaStore, &rolegroup_ref)
+            .unwrap();
+
+        build_metastore_rolegroup_statefulset(
+            hive,
+            &HiveRole::MetaStore,
+            &rolegroup_product_image(&merged_config, &resolved_product_image),
+            &rolegroup_ref,
+            &rolegroup_config(hive, rolegroup_name),
+            None,
+            &merged_config,
+            "hive-serviceaccount",
+        )
+        .unwrap()
+    }
+
+    /// The validated product config of the given role group
+    fn rolegroup_config(
+        hive: &HiveCluster,
+        rolegroup_name: &str,
+    ) -> HashMap<PropertyNameKind, BTreeMap<String, String>> {
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+        let product_config =
+            ProductConfigManager::from_yaml_file("../../deploy/config-spec/properties.yaml")
+                .unwrap();
+        let mut validated_config = validated_product_config(
+            hive,
+            &resolved_product_image.product_version,
+            &product_config,
+        )
+        .unwrap();
+
+        validated_config
+            .remove(&HiveRole::MetaStore.to_string())
+            .and_then(|mut role_config| role_config.remove(rolegroup_name))
+            .expect("role group must exist")
+    }
+
+    fn build_config_map(hive: &HiveCluster, rolegroup_name: &str) -> ConfigMap {
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+        let rolegroup_ref = hive.metastore_rolegroup_ref(rolegroup_name);
+        let merged_config = hive
+            .merged_config(&HiveRole::MetaStore, &rolegroup_ref)
+            .unwrap();
+        let cluster_info = cluster_info(
+            hive,
+            &KubernetesClusterInfo {
+                cluster_domain: DomainName::try_from("cluster.local").unwrap(),
+            },
+        )
+        .unwrap();
+
+        build_metastore_rolegroup_config_map(
+            hive,
+            "default",
+            &resolved_product_image,
+            &rolegroup_ref,
+            &rolegroup_config(hive, rolegroup_name),
+            None,
+            &merged_config,
+            None,
+            &cluster_info,
+        )
+        .unwrap()
+    }
+
+    /// The value of the given property in the `hive-site.xml` of the ConfigMap
+    fn hive_site_property(config_map: &ConfigMap, name: &str) -> Option<String> {
+        let hive_site = &config_map.data.as_ref()?[HIVE_SITE_XML];
+        let (_, rest) = hive_site.split_once(&format!("<name>{name}</name>"))?;
+        let (_, rest) = rest.split_once("<value>")?;
+        let (value, _) = rest.split_once("</value>")?;
+
+        Some(value.to_string())
+    }
+
+    fn hive_container(
+        statefulset: &StatefulSet,
+    ) -> &stackable_operator::k8s_openapi::api::core::v1::Container {
+        statefulset
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.template.spec.as_ref())
+            .and_then(|pod_spec| pod_spec.containers.iter().find(|c| c.name == APP_NAME))
+            .expect("hive container must exist")
+    }
+
+    #[rstest]
+    #[case::partially_ready(Some(3), Some((3, Some(2))), 3, 2)]
+    #[case::without_ready_replicas(Some(2), Some((2, None)), 2, 0)]
+    #[case::without_status(Some(2), None, 2, 0)]
+    #[case::kubernetes_default_replicas(None, None, 1, 0)]
+    fn test_rolegroup_status(
+        #[case] replicas: Option<i32>,
+        #[case] status: Option<(i32, Option<i32>)>,
+        #[case] desired_replicas: i32,
+        #[case] ready_replicas: i32,
+    ) {
+        let statefulset = StatefulSet {
+            spec: Some(StatefulSetSpec {
+                replicas,
+                ..StatefulSetSpec::default()
+            }),
+            status: status.map(|(replicas, ready_replicas)| StatefulSetStatus {
+                replicas,
+                ready_replicas,
+                ..StatefulSetStatus::default()
+            }),
+            ..StatefulSet::default()
+        };
+
+        assert_eq!(
+            build_rolegroup_status(&statefulset),
+            RolegroupStatus {
+                desired_replicas,
+                ready_replicas,
+            }
+        );
+    }
+
+    #[test]
+    fn test_additional_volume_claim_templates() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                resources:
+                  storage:
+                    additionalVolumes:
+                      cache:
+                        mountPath: /stackable/cache
+                        capacity: 2Gi
+                        storageClass: fast
+              roleGroups:
+                default:
+                  replicas: 1
+                  config:
+                    resources:
+                      storage:
+                        additionalVolumes:
+                          # Only overrides the capacity of the volume defined on the role
+                          cache:
+                            capacity: 5Gi
+                          scratch:
+                            mountPath: /stackable/scratch
+                            capacity: 1Gi
+                plain:
+                  replicas: 1
+            "#,
+        );
+        let statefulset = build_statefulset(&hive, "default");
+
+        let volume_claim_templates = statefulset
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.volume_claim_templates.as_ref())
+            .expect("volume claim templates must be set");
+        assert_eq!(
+            volume_claim_templates
+                .iter()
+                .map(|pvc| {
+                    let spec = pvc.spec.as_ref().unwrap();
+                    (
+                        pvc.metadata.name.as_deref().unwrap(),
+                        spec.resources
+                            .as_ref()
+                            .and_then(|resources| resources.requests.as_ref())
+                            .and_then(|requests| requests.get("storage"))
+                            .map(|quantity| quantity.0.as_str()),
+                        spec.storage_class_name.as_deref(),
+                        spec.access_modes.clone(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "cache",
+                    Some("5Gi"),
+                    Some("fast"),
+                    Some(vec!["ReadWriteOnce".to_string()])
+                ),
+                (
+                    "scratch",
+                    Some("1Gi"),
+                    None,
+                    Some(vec!["ReadWriteOnce".to_string()])
+                ),
+            ]
+        );
+
+        let volume_mounts = hive_container(&statefulset)
+            .volume_mounts
+            .as_ref()
+            .expect("volume mounts must be set");
+        for (name, mount_path) in [
+            ("cache", "/stackable/cache"),
+            ("scratch", "/stackable/scratch"),
+        ] {
+            assert!(
+                volume_mounts
+                    .iter()
+                    .any(|mount| mount.name == name && mount.mount_path == mount_path),
+                "missing volume mount {name}"
+            );
+        }
+
+        // The role level volume is created for every role group
+        let statefulset = build_statefulset(&hive, "plain");
+        assert_eq!(
+            statefulset
+                .spec
+                .and_then(|spec| spec.volume_claim_templates)
+                .map(|templates| templates
+                    .into_iter()
+                    .filter_map(|pvc| pvc.metadata.name)
+                    .collect::<Vec<_>>()),
+            Some(vec!["cache".to_string()])
+        );
+    }
+
+    #[rstest]
+    #[case::dry_run(true, false, ClusterResourceApplyStrategy::NoApply)]
+    #[case::dry_run_paused(true, true, ClusterResourceApplyStrategy::NoApply)]
+    #[case::paused(false, true, ClusterResourceApplyStrategy::ReconciliationPaused)]
+    #[case::default(false, false, ClusterResourceApplyStrategy::Default)]
+    fn test_cluster_resource_apply_strategy(
+        #[case] dry_run: bool,
+        #[case] reconciliation_paused: bool,
+        #[case] expected: ClusterResourceApplyStrategy,
+    ) {
+        let mut spec = serde_yaml::from_str::<serde_yaml::Value>(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        )
+        .unwrap();
+        spec["clusterOperation"]["reconciliationPaused"] = reconciliation_paused.into();
+        let hive = hive_from_yaml(&serde_yaml::to_string(&spec).unwrap());
+
+        assert_eq!(cluster_resource_apply_strategy(&hive, dry_run), expected);
+    }
+
+    #[rstest]
+    #[case::dry_run(true, "{}", &[])]
+    #[case::dry_run_enabled(
+        true,
+        "{networkPolicy: {enabled: true}, monitoring: {serviceMonitor: {enabled: true}}}",
+        &[]
+    )]
+    #[case::default(
+        false,
+        "{}",
+        &[
+            OutOfBandOperation::ApplyHorizontalPodAutoscalers,
+            OutOfBandOperation::DeleteOrphanedHorizontalPodAutoscalers,
+            OutOfBandOperation::DeleteNetworkPolicy,
+            OutOfBandOperation::DeleteServiceMonitor,
+            OutOfBandOperation::PatchStatus,
+        ]
+    )]
+    #[case::enabled(
+        false,
+        "{networkPolicy: {enabled: true}, monitoring: {serviceMonitor: {enabled: true}}}",
+        &[
+            OutOfBandOperation::ApplyHorizontalPodAutoscalers,
+            OutOfBandOperation::DeleteOrphanedHorizontalPodAutoscalers,
+            OutOfBandOperation::ApplyNetworkPolicy,
+            OutOfBandOperation::PatchStatus,
+        ]
+    )]
+    fn test_out_of_band_operations(
+        #[case] dry_run: bool,
+        #[case] cluster_config: &str,
+        #[case] expected: &[OutOfBandOperation],
+    ) {
+        let hive = hive_from_yaml(&format!("clusterConfig: {cluster_config}"));
+
+        assert_eq!(
+            out_of_band_operations(&hive, dry_run),
+            BTreeSet::from_iter(expected.iter().copied())
+        );
+    }
+
+    #[test]
+    fn test_rolegroup_image_override() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+                canary:
+                  replicas: 1
+                  config:
+                    image:
+                      productVersion: 4.0.1
+            "#,
+        );
+
+        let default_statefulset = build_statefulset(&hive, "default");
+        let canary_statefulset = build_statefulset(&hive, "canary");
+        let default_image = hive_container(&default_statefulset)
+            .image
+            .as_deref()
+            .unwrap();
+        let canary_image = hive_container(&canary_statefulset)
+            .image
+            .as_deref()
+            .unwrap();
+
+        assert!(
+            default_image.contains(":4.0.0-stackable"),
+            "unexpected image {default_image}"
+        );
+        assert!(
+            canary_image.contains(":4.0.1-stackable"),
+            "unexpected image {canary_image}"
+        );
+        assert_eq!(
+            canary_statefulset
+                .metadata
+                .labels
+                .as_ref()
+                .and_then(|labels| labels.get("app.kubernetes.io/version"))
+                .map(String::as_str),
+            Some(canary_image.rsplit(':').next().unwrap())
+        );
+    }
+
+    #[rstest]
+    #[case::missing(None, Some("does not exist"))]
+    #[case::missing_key(Some(&[CORE_SITE_XML][..]), Some("does not contain the key [hdfs-site.xml]"))]
+    #[case::complete(Some(&[CORE_SITE_XML, HDFS_SITE_XML][..]), None)]
+    fn test_validate_hdfs_config_map(
+        #[case] keys: Option<&[&str]>,
+        #[case] expected_error: Option<&str>,
+    ) {
+        let config_map = keys.map(|keys| ConfigMap {
+            data: Some(
+                keys.iter()
+                    .map(|key| (key.to_string(), "<configuration/>".to_string()))
+                    .collect(),
+            ),
+            ..ConfigMap::default()
+        });
+
+        let result = validate_hdfs_config_map("hdfs", config_map.as_ref());
+
+        match expected_error {
+            Some(expected_error) => {
+                let error = result.unwrap_err().to_string();
+                assert!(error.contains("[hdfs]"), "unexpected error {error}");
+                assert!(error.contains(expected_error), "unexpected error {error}");
+            }
+            None => assert!(result.is_ok()),
+        }
+    }
+
+    #[test]
+    fn test_db_credentials_from_csi_volume() {
+        let mut hive = hive_from_yaml(
+            r#"
+            clusterConfig:
+              database:
+                credentialsCsiVolume:
+                  volumeAttributes:
+                    secretProviderClass: hive-db-credentials
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+        hive.spec.cluster_config.database.credentials_secret = None;
+
+        let statefulset = build_statefulset(&hive, "default");
+        let container = hive_container(&statefulset);
+        let volume = statefulset
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.template.spec.as_ref())
+            .and_then(|pod_spec| pod_spec.volumes.as_ref())
+            .and_then(|volumes| volumes.iter().find(|v| v.name == DB_CREDENTIALS_DIR_NAME))
+            .expect("db credentials volume must exist");
+        let csi = volume.csi.as_ref().unwrap();
+
+        assert_eq!(csi.driver, "secrets-store.csi.k8s.io");
+        assert_eq!(
+            csi.volume_attributes,
+            Some(BTreeMap::from([(
+                "secretProviderClass".to_string(),
+                "hive-db-credentials".to_string()
+            )]))
+        );
+        assert!(container
+            .volume_mounts
+            .iter()
+            .flatten()
+            .any(|mount| mount.name == DB_CREDENTIALS_DIR_NAME
+                && mount.mount_path == DB_CREDENTIALS_DIR));
+        assert!(!container
+            .env
+            .iter()
+            .flatten()
+            .any(|env| env.name == DB_PASSWORD_ENV));
+    }
+
+    #[test]
+    fn test_additional_config_files() {
+        let hive = hive_from_yaml(
+            r#"
+            clusterConfig:
+              additionalConfigFiles:
+                configMap: hive-extra-config
+                files:
+                  - ranger-hive-security.xml
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        let statefulset = build_statefulset(&hive, "default");
+        let container = hive_container(&statefulset);
+        let volume = statefulset
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.template.spec.as_ref())
+            .and_then(|pod_spec| pod_spec.volumes.as_ref())
+            .and_then(|volumes| {
+                volumes
+                    .iter()
+                    .find(|v| v.name == STACKABLE_CONFIG_MOUNT_DIR_NAME)
+            })
+            .expect("config mount volume must exist");
+        let config_maps = volume
+            .projected
+            .iter()
+            .flat_map(|projected| projected.sources.iter().flatten())
+            .filter_map(|source| source.config_map.as_ref())
+            .collect::<Vec<_>>();
+
+        assert_eq!(volume.config_map, None);
+        assert_eq!(
+            config_maps
+                .iter()
+                .map(|config_map| config_map.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["simple-hive-metastore-default", "hive-extra-config"]
+        );
+        assert_eq!(
+            config_maps[1].items,
+            Some(vec![KeyToPath {
+                key: "ranger-hive-security.xml".to_string(),
+                path: "ranger-hive-security.xml".to_string(),
+                ..KeyToPath::default()
+            }])
+        );
+        assert!(container
+            .volume_mounts
+            .iter()
+            .flatten()
+            .any(|mount| mount.name == STACKABLE_CONFIG_MOUNT_DIR_NAME
+                && mount.mount_path == STACKABLE_CONFIG_MOUNT_DIR));
+        assert!(container
+            .args
+            .iter()
+            .flatten()
+            .any(|arg| arg.contains("cp -RL /stackable/mount/config/* /stackable/config")));
+    }
+
+    #[test]
+    fn test_s3_proxy_credentials() {
+        let hive = hive_from_yaml(
+            r#"
+            clusterConfig:
+              s3Tuning:
+                proxy:
+                  host: proxy.example.com
+                  credentialsSecret: s3-proxy-credentials
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        let statefulset = build_statefulset(&hive, "default");
+        let env = hive_container(&statefulset).env.clone().unwrap_or_default();
+
+        for (name, key) in [
+            (S3_PROXY_USERNAME_ENV, "username"),
+            (S3_PROXY_PASSWORD_ENV, "password"),
+        ] {
+            assert!(env.contains(&env_var_from_secret(name, "s3-proxy-credentials", key)));
+        }
+    }
+
+    #[test]
+    fn test_s3_endpoint_secret() {
+        let hive = hive_from_yaml(
+            r#"
+            clusterConfig:
+              s3Tuning:
+                endpointSecret: s3-endpoint
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        let statefulset = build_statefulset(&hive, "default");
+        let env = hive_container(&statefulset).env.clone().unwrap_or_default();
+
+        for (name, key) in [(S3_ENDPOINT_ENV, "endpoint"), (S3_REGION_ENV, "region")] {
+            assert!(env.contains(&env_var_from_secret(name, "s3-endpoint", key)));
+        }
+        assert_eq!(
+            hive_site_property(&build_config_map(&hive, "default"), "fs.s3a.endpoint").as_deref(),
+            Some("${env:S3_ENDPOINT}")
+        );
+    }
+
+    #[test]
+    fn test_reconcile_action() {
+        assert_eq!(reconcile_action(None), Action::await_change());
+        assert_eq!(
+            reconcile_action(Some(Duration::from_minutes_unchecked(10))),
+            Action::requeue(std::time::Duration::from_secs(600))
+        );
+    }
+
+    #[test]
+    fn test_replicas_default_to_one() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default: {}
+            "#,
+        );
+
+        let statefulset = build_statefulset(&hive, "default");
+
+        assert_eq!(statefulset.spec.and_then(|spec| spec.replicas), Some(1));
+    }
+
+    #[test]
+    fn test_derby_with_multiple_replicas_is_rejected() {
+        let hive = hive_from_yaml(
+            r#"
+            clusterConfig:
+              database:
+                connString: jdbc:derby:;databaseName=/tmp/hive;create=true
+                dbType: derby
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+                other:
+                  replicas: 1
+            "#,
+        );
+
+        assert!(matches!(
+            validate_replicas(&hive),
+            Err(Error::DerbyWithMultipleReplicas { replicas: 2 })
+        ));
+    }
+
+    #[rstest]
+    #[case::disabled(true, "minReplicas: 4", None)]
+    #[case::derby(true, "maxReplicas: 3", Some("DerbyWithAutoscaling"))]
+    #[case::only_max(false, "maxReplicas: 3", None)]
+    #[case::equal_bounds(false, "{minReplicas: 3, maxReplicas: 3}", None)]
+    #[case::out_of_order(
+        false,
+        "{minReplicas: 4, maxReplicas: 3}",
+        Some("AutoscalingReplicasOutOfOrder")
+    )]
+    fn test_validate_autoscaling(
+        #[case] derby: bool,
+        #[case] autoscaling: &str,
+        #[case] expected_error: Option<&str>,
+    ) {
+        let database = match derby {
+            true => "{connString: 'jdbc:derby:;databaseName=/tmp/hive;create=true', dbType: derby}",
+            false => "{}",
+        };
+        let hive = hive_from_yaml(&format!(
+            r#"
+            clusterConfig:
+              database: {database}
+            metastore:
+              roleGroups:
+                default:
+                  config:
+                    autoscaling: {autoscaling}
+            "#
+        ));
+        let rolegroup = hive.metastore_rolegroup_ref("default");
+        let config = hive
+            .merged_config(&HiveRole::MetaStore, &rolegroup)
+            .unwrap();
+
+        let error = validate_autoscaling(&hive, &rolegroup, &config.autoscaling)
+            .err()
+            .map(|error| -> &'static str { ErrorDiscriminants::from(&error).into() });
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_postgres_with_multiple_replicas_is_accepted() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 3
+            "#,
+        );
+
+        assert!(validate_replicas(&hive).is_ok());
+    }
+
+    #[rstest]
+    // The role group Services publish the addresses of not ready Pods for the metastore peers
+    #[case::default("{}", None, Some(true))]
+    #[case::configured(
+        "{role: {publishNotReadyAddresses: true}, roleGroup: {publishNotReadyAddresses: false}}",
+        Some(true),
+        Some(false)
+    )]
+    #[case::only_role("{role: {publishNotReadyAddresses: false}}", Some(false), Some(true))]
+    fn test_publish_not_ready_addresses(
+        #[case] services: &str,
+        #[case] expected_role: Option<bool>,
+        #[case] expected_rolegroup: Option<bool>,
+    ) {
+        let hive = hive_from_yaml(&format!("clusterConfig: {{services: {services}}}"));
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+
+        let role_service = build_metastore_role_service(&hive, &resolved_product_image).unwrap();
+        let rolegroup_service = build_rolegroup_service(
+            &hive,
+            &resolved_product_image,
+            &hive.metastore_rolegroup_ref("default"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            role_service.spec.unwrap().publish_not_ready_addresses,
+            expected_role
+        );
+        assert_eq!(
+            rolegroup_service.spec.unwrap().publish_not_ready_addresses,
+            expected_rolegroup
+        );
+    }
+
+    #[test]
+    fn test_session_affinity() {
+        let hive = hive_from_yaml("{}");
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+
+        let role_service = build_metastore_role_service(&hive, &resolved_product_image).unwrap();
+
+        assert_eq!(role_service.spec.unwrap().session_affinity, None);
+
+        let hive = hive_from_yaml(
+            r#"
+            clusterConfig:
+              services:
+                role:
+                  sessionAffinity: ClientIP
+                roleGroup:
+                  sessionAffinity: ClientIP
+            "#,
+        );
+
+        let role_service = build_metastore_role_service(&hive, &resolved_product_image).unwrap();
+        let rolegroup_service = build_rolegroup_service(
+            &hive,
+            &resolved_product_image,
+            &hive.metastore_rolegroup_ref("default"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            role_service.spec.unwrap().session_affinity.as_deref(),
+            Some("ClientIP")
+        );
+        // Session affinity has no effect on headless Services, so it is not set on them
+        assert_eq!(rolegroup_service.spec.unwrap().session_affinity, None);
+    }
+
+    #[test]
+    fn test_umask_mode_in_core_site() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                umaskMode: "027"
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        let config_map = build_config_map(&hive, "default");
+        let core_site = &config_map.data.as_ref().unwrap()[CORE_SITE_XML];
+
+        assert!(core_site.contains("<name>fs.permissions.umask-mode</name>"));
+        assert!(core_site.contains("<value>027</value>"));
+    }
+
+    #[test]
+    fn test_render_jvm_args() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                resources:
+                  memory:
+                    limit: 1Gi
+              roleGroups:
+                default:
+                  replicas: 1
+                debug:
+                  replicas: 1
+                  config:
+                    renderJvmArgs: true
+                  envOverrides:
+                    HADOOP_OPTS: -XX:+HeapDumpOnOutOfMemoryError
+            "#,
+        );
+
+        let config_map = build_config_map(&hive, "debug");
+        let jvm_args = config_map.data.as_ref().unwrap()[JVM_ARGS_FILE]
+            .lines()
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            jvm_args,
+            vec!["-Xmx819m", "-XX:+HeapDumpOnOutOfMemoryError"]
+        );
+        assert!(!build_config_map(&hive, "default")
+            .data
+            .unwrap()
+            .contains_key(JVM_ARGS_FILE));
+    }
+
+    #[rstest]
+    #[case::generated(None, None, Some(true))]
+    #[case::disabled(Some(false), None, None)]
+    #[case::disabled_with_overrides(Some(false), Some("simple"), Some(false))]
+    fn test_kerberos_core_site(
+        #[case] generate_core_site: Option<bool>,
+        #[case] authentication_override: Option<&str>,
+        #[case] expected_generated: Option<bool>,
+    ) {
+        let mut spec = serde_yaml::from_str::<serde_yaml::Value>(
+            r#"
+            clusterConfig:
+              authentication:
+                kerberos:
+                  secretClass: kerberos
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        )
+        .unwrap();
+        if let Some(generate_core_site) = generate_core_site {
+            spec["clusterConfig"]["authentication"]["kerberos"]["generateCoreSite"] =
+                generate_core_site.into();
+        }
+        if let Some(authentication_override) = authentication_override {
+            spec["metastore"]["configOverrides"][CORE_SITE_XML]["hadoop.security.authentication"] =
+                authentication_override.into();
+        }
+        let hive = hive_from_yaml(&serde_yaml::to_string(&spec).unwrap());
+
+        let config_map = build_config_map(&hive, "default");
+        let core_site = config_map.data.as_ref().unwrap().get(CORE_SITE_XML);
+
+        match expected_generated {
+            Some(generated) => {
+                let core_site = core_site.expect("the core-site.xml must be present");
+                assert!(core_site.contains("<name>hadoop.security.authentication</name>"));
+                assert_eq!(core_site.contains("<value>kerberos</value>"), generated);
+                assert_eq!(core_site.contains("<value>simple</value>"), !generated);
+            }
+            None => assert!(core_site.is_none()),
+        }
+    }
+
+    #[test]
+    fn test_no_core_site_by_default() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        let config_map = build_config_map(&hive, "default");
+
+        assert!(!config_map.data.unwrap().contains_key(CORE_SITE_XML));
+    }
+
+    #[test]
+    fn test_command_wrapper() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                commandWrapper: /stackable/profiler.sh --output /tmp/profile
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        let statefulset = build_statefulset(&hive, "default");
+        let args = hive_container(&statefulset)
+            .args
+            .as_ref()
+            .unwrap()
+            .join("\n");
+
+        assert!(args.contains(&format!(
+            "bin/base --config \"{STACKABLE_CONFIG_DIR}\" --service schemaTool"
+        )));
+        assert!(args.contains(&format!(
+            "/stackable/profiler.sh --output /tmp/profile bin/base --config \"{STACKABLE_CONFIG_DIR}\" --service metastore &"
+        )));
+        assert!(!args
+            .contains("/tmp/profile bin/base --config \"/stackable/config\" --service schemaTool"));
+    }
+
+    #[test]
+    fn test_term_signal_forwarded_to_metastore() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        let statefulset = build_statefulset(&hive, "default");
+        let args = hive_container(&statefulset)
+            .args
+            .as_ref()
+            .unwrap()
+            .join("\n");
+
+        let start = args
+            .find("--service metastore &")
+            .expect("metastore is not started");
+        let forward = args
+            .find(r#"kill -TERM "$METASTORE_PID""#)
+            .expect("SIGTERM is not forwarded to the metastore");
+        let wait = args
+            .find("wait_for_termination $METASTORE_PID")
+            .expect("the metastore process is not awaited");
+        assert!(args[start..forward].contains("METASTORE_PID=$!"));
+        assert!(forward < wait);
+    }
+
+    #[test]
+    fn test_metastore_start_command_hive_3() {
+        assert_eq!(
+            metastore_start_command("3.1.3", &DbType::Postgres, None),
+            "bin/start-metastore --config /stackable/config --db-type postgres --hive-bin-dir bin &"
+        );
+        assert_eq!(
+            metastore_start_command("3.1.3", &DbType::Postgres, Some("numactl --interleave=all")),
+            "numactl --interleave=all bin/start-metastore --config /stackable/config --db-type postgres --hive-bin-dir bin &"
+        );
+    }
+
+    #[rstest]
+    #[case::hive_3(
+        "3.1.3",
+        "METASTORE_CONF_DIR=/stackable/config bin/schematool -dbType postgres -info"
+    )]
+    #[case::hive_4(
+        "4.0.0",
+        "bin/base --config \"/stackable/config\" --service schemaTool -dbType \"postgres\" -info"
+    )]
+    fn test_schema_tool_readiness_probe(#[case] product_version: &str, #[case] expected: &str) {
+        let probe = readiness_probe(
+            product_version,
+            &DbType::Postgres,
+            Some(ReadinessCheck::SchemaTool),
+        );
+
+        assert_eq!(probe.tcp_socket, None);
+        assert_eq!(
+            probe.exec.and_then(|exec| exec.command),
+            Some(vec![
+                "/bin/bash".to_string(),
+                "-c".to_string(),
+                expected.to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_thrift_port_readiness_probe() {
+        let probe = readiness_probe("4.0.0", &DbType::Postgres, None);
+
+        // The probe of existing StatefulSets must not change unless the check is configured
+        assert_eq!(
+            probe,
+            Probe {
+                initial_delay_seconds: Some(10),
+                period_seconds: Some(10),
+                failure_threshold: Some(5),
+                tcp_socket: Some(TCPSocketAction {
+                    port: IntOrString::String("hive".to_string()),
+                    ..TCPSocketAction::default()
+                }),
+                ..Probe::default()
+            }
+        );
+        assert_eq!(
+            readiness_probe("4.0.0", &DbType::Postgres, Some(ReadinessCheck::ThriftPort)),
+            probe
+        );
+    }
+
+    #[rstest]
+    #[case::request_and_limit("{request: 1Gi, limit: 2Gi}", "{}", Some("1Gi"), Some("2Gi"))]
+    #[case::only_limit("{limit: 2Gi}", "{}", None, Some("2Gi"))]
+    #[case::limit_on_role_group(
+        "{request: 1Gi, limit: 2Gi}",
+        "{limit: 4Gi}",
+        Some("1Gi"),
+        Some("4Gi")
+    )]
+    #[case::default("{}", "{}", None, None)]
+    fn test_ephemeral_storage_resources(
+        #[case] ephemeral: &str,
+        #[case] role_group_ephemeral: &str,
+        #[case] expected_request: Option<&str>,
+        #[case] expected_limit: Option<&str>,
+    ) {
+        let hive = hive_from_yaml(&format!(
+            r#"
+            metastore:
+              config:
+                resources:
+                  storage:
+                    ephemeral: {ephemeral}
+              roleGroups:
+                default:
+                  replicas: 1
+                  config:
+                    resources:
+                      storage:
+                        ephemeral: {role_group_ephemeral}
+            "#
+        ));
+
+        let statefulset = build_statefulset(&hive, "default");
+        let resources = hive_container(&statefulset).resources.as_ref().unwrap();
+
+        assert_eq!(
+            resources
+                .requests
+                .as_ref()
+                .unwrap()
+                .get("ephemeral-storage"),
+            expected_request
+                .map(|request| Quantity(request.to_string()))
+                .as_ref()
+        );
+        assert_eq!(
+            resources.limits.as_ref().unwrap().get("ephemeral-storage"),
+            expected_limit
+                .map(|limit| Quantity(limit.to_string()))
+                .as_ref()
+        );
+        // The generic resources are kept
+        assert!(resources.limits.as_ref().unwrap().contains_key("memory"));
+    }
+
+    #[rstest]
+    #[case::explicit("512Mi", "512Mi")]
+    #[case::defaults_to_limit("null", "2Gi")]
+    fn test_memory_request(#[case] memory_request: &str, #[case] expected: &str) {
+        let hive = hive_from_yaml(&format!(
+            r#"
+            metastore:
+              config:
+                memoryRequest: {memory_request}
+                resources:
+                  memory:
+                    limit: 2Gi
+              roleGroups:
+                default:
+                  replicas: 1
+            "#
+        ));
+
+        let statefulset = build_statefulset(&hive, "default");
+        let resources = hive_container(&statefulset).resources.as_ref().unwrap();
+
+        assert_eq!(
+            resources.requests.as_ref().unwrap().get("memory"),
+            Some(&Quantity(expected.to_string()))
+        );
+        assert_eq!(
+            resources.limits.as_ref().unwrap().get("memory"),
+            Some(&Quantity("2Gi".to_string()))
+        );
+        // The heap is derived from the limit, independent of the request
+        let config_map = build_config_map(&hive, "default");
+        assert!(config_map.data.unwrap()[HIVE_ENV_SH].contains("export HADOOP_HEAPSIZE=1638"));
+    }
+
+    #[test]
+    fn test_trust_store_password_from_secret() {
+        let hive = hive_from_yaml(
+            r#"
+            clusterConfig:
+              trustStorePasswordSecret: hive-truststore
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        let statefulset = build_statefulset(&hive, "default");
+        let container = hive_container(&statefulset);
+        let env = container.env.as_ref().unwrap();
+
+        let password = env
+            .iter()
+            .find(|env_var| env_var.name == TRUST_STORE_PASSWORD_ENV)
+            .expect("truststore password env var must exist");
+        let hadoop_opts = env
+            .iter()
+            .find(|env_var| env_var.name == "HADOOP_OPTS")
+            .and_then(|env_var| env_var.value.as_deref())
+            .expect("HADOOP_OPTS env var must exist");
+
+        assert_eq!(
+            password,
+            &env_var_from_secret(TRUST_STORE_PASSWORD_ENV, "hive-truststore", "password")
+        );
+        // The password must not be visible in the command line of the JVM
+        assert!(!hadoop_opts.contains("trustStorePassword"));
+
+        let args = container.args.as_ref().unwrap().join("\n");
+        assert!(args.contains("-deststorepass \"$TRUST_STORE_PASSWORD\""));
+        assert!(!args.contains(&format!("-deststorepass {STACKABLE_TRUST_STORE_PASSWORD}")));
+    }
+
+    #[test]
+    fn test_default_trust_store_password() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        assert_eq!(
+            trust_store_password_env_var(&hive),
+            EnvVar {
+                name: TRUST_STORE_PASSWORD_ENV.to_string(),
+                value: Some("changeit".to_string()),
+                value_from: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_run_as_user_and_group() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+                other:
+                  replicas: 1
+                  config:
+                    securityContext:
+                      runAsUser: 2000
+                      runAsGroup: 3000
+            "#,
+        );
+
+        let security_context = |rolegroup_name: &str| {
+            build_statefulset(&hive, rolegroup_name)
+                .spec
+                .and_then(|spec| spec.template.spec)
+                .and_then(|pod_spec| pod_spec.security_context)
+                .expect("the pod security context must be set")
+        };
+
+        let default = security_context("default");
+        assert_eq!(default.run_as_user, Some(HIVE_UID));
+        assert_eq!(default.run_as_group, Some(0));
+        assert_eq!(default.run_as_non_root, Some(true));
+
+        let other = security_context("other");
+        assert_eq!(other.run_as_user, Some(2000));
+        assert_eq!(other.run_as_group, Some(3000));
+        assert_eq!(other.run_as_non_root, Some(true));
+    }
+
+    #[test]
+    fn test_fs_group_change_policy() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                securityContext:
+                  fsGroupChangePolicy: OnRootMismatch
+              roleGroups:
+                default:
+                  replicas: 1
+                other:
+                  replicas: 1
+                  config:
+                    securityContext:
+                      fsGroupChangePolicy: Always
+            "#,
+        );
+
+        let fs_group_change_policy = |rolegroup_name: &str| {
+            build_statefulset(&hive, rolegroup_name)
+                .spec
+                .and_then(|spec| spec.template.spec)
+                .and_then(|pod_spec| pod_spec.security_context)
+                .and_then(|security_context| security_context.fs_group_change_policy)
+        };
+
+        assert_eq!(
+            fs_group_change_policy("default").as_deref(),
+            Some("OnRootMismatch")
+        );
+        assert_eq!(fs_group_change_policy("other").as_deref(), Some("Always"));
+    }
+
+    #[test]
+    fn test_pod_annotations() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                podAnnotations:
+                  sidecar.istio.io/inject: "true"
+              roleGroups:
+                default:
+                  replicas: 1
+                  config:
+                    podAnnotations:
+                      linkerd.io/inject: enabled
+            "#,
+        );
+
+        let statefulset = build_statefulset(&hive, "default");
+
+        assert_eq!(
+            statefulset
+                .spec
+                .and_then(|spec| spec.template.metadata)
+                .and_then(|metadata| metadata.annotations),
+            Some(BTreeMap::from([
+                ("linkerd.io/inject".to_string(), "enabled".to_string()),
+                ("sidecar.istio.io/inject".to_string(), "true".to_string()),
+            ]))
+        );
+        assert_eq!(statefulset.metadata.annotations, None);
+    }
+
+    #[rstest]
+    #[case::default("{}", true)]
+    #[case::disabled("{metricsService: {enabled: false}}", false)]
+    fn test_rolegroup_metrics_service(#[case] monitoring: &str, #[case] enabled: bool) {
+        let hive = hive_from_yaml(&format!(
+            r#"
+            clusterConfig:
+              monitoring: {monitoring}
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+                secondary:
+                  replicas: 1
+            "#
+        ));
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+        let rolegroup_ref = hive.metastore_rolegroup_ref("secondary");
+
+        // The role group Service keeps exposing the metrics port to existing scrape configs
+        let rolegroup_service =
+            build_rolegroup_service(&hive, &resolved_product_image, &rolegroup_ref).unwrap();
+        assert_eq!(
+            rolegroup_service
+                .metadata
+                .labels
+                .unwrap()
+                .get("prometheus.io/scrape")
+                .map(String::as_str),
+            Some("true")
+        );
+        assert_eq!(rolegroup_service.spec.unwrap().ports, Some(service_ports()));
+
+        let metrics_service =
+            build_rolegroup_metrics_service(&hive, &resolved_product_image, &rolegroup_ref)
+                .unwrap();
+        let Some(metrics_service) = metrics_service else {
+            assert!(!enabled, "the metrics Service must be enabled");
+            return;
+        };
+        assert!(enabled, "the metrics Service must be disabled");
+
+        // The name must not clash with the role group Service
+        assert_eq!(
+            metrics_service.metadata.name.as_deref(),
+            Some("simple-hive-metastore-secondary-metrics")
+        );
+        // Otherwise Prometheus would scrape the Pods twice
+        assert_eq!(
+            metrics_service
+                .metadata
+                .labels
+                .unwrap()
+                .get("prometheus.io/scrape"),
+            None
+        );
+        let spec = metrics_service.spec.unwrap();
+        assert_eq!(spec.ports, Some(vec![metrics_service_port()]));
+        // The Service covers every Pod of the role group, but not the ones of other role groups
+        assert_eq!(spec.cluster_ip.as_deref(), Some("None"));
+        assert_eq!(
+            spec.selector
+                .unwrap()
+                .get("app.kubernetes.io/role-group")
+                .map(String::as_str),
+            Some("secondary")
+        );
+    }
+
+    #[test]
+    fn test_working_dir() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                workingDir: /stackable/work
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        let statefulset = build_statefulset(&hive, "default");
+
+        assert_eq!(
+            hive_container(&statefulset).working_dir.as_deref(),
+            Some("/stackable/work")
+        );
+    }
+
+    #[test]
+    fn test_pre_stop_command() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+                draining:
+                  replicas: 1
+                  config:
+                    preStopCommand: sleep 15
+            "#,
+        );
+
+        let draining = build_statefulset(&hive, "draining");
+        let default = build_statefulset(&hive, "default");
+
+        assert_eq!(
+            hive_container(&draining)
+                .lifecycle
+                .as_ref()
+                .and_then(|lifecycle| lifecycle.pre_stop.as_ref())
+                .and_then(|pre_stop| pre_stop.exec.as_ref())
+                .and_then(|exec| exec.command.as_deref()),
+            Some(
+                [
+                    "/bin/bash".to_string(),
+                    "-c".to_string(),
+                    "sleep 15".to_string()
+                ]
+                .as_slice()
+            )
+        );
+        assert_eq!(hive_container(&default).lifecycle, None);
+    }
+
+    #[rstest]
+    #[case::kerberos_default(true, None, Some("true"))]
+    #[case::kerberos_without_sasl(true, Some(false), Some("false"))]
+    #[case::sasl_without_kerberos(false, Some(true), Some("true"))]
+    #[case::neither(false, None, None)]
+    fn test_sasl_enabled(
+        #[case] kerberos: bool,
+        #[case] sasl_enabled: Option<bool>,
+        #[case] expected: Option<&str>,
+    ) {
+        let mut spec = serde_yaml::from_str::<serde_yaml::Value>(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        )
+        .unwrap();
+        if kerberos {
+            spec["clusterConfig"]["authentication"]["kerberos"]["secretClass"] = "kerberos".into();
+        }
+        if let Some(sasl_enabled) = sasl_enabled {
+            spec["metastore"]["config"]["saslEnabled"] = sasl_enabled.into();
+        }
+        let hive = hive_from_yaml(&serde_yaml::to_string(&spec).unwrap());
+
+        let config_map = build_config_map(&hive, "default");
+
+        assert_eq!(
+            hive_site_property(&config_map, "hive.metastore.sasl.enabled").as_deref(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::read_only("true", "null", true)]
+    #[case::writable_role_group("true", "false", false)]
+    #[case::default("null", "null", false)]
+    fn test_read_only_root_filesystem(
+        #[case] read_only_root_filesystem: &str,
+        #[case] role_group_read_only_root_filesystem: &str,
+        #[case] read_only: bool,
+    ) {
+        let hive = hive_from_yaml(&format!(
+            r#"
+            metastore:
+              config:
+                securityContext:
+                  readOnlyRootFilesystem: {read_only_root_filesystem}
+              roleGroups:
+                default:
+                  replicas: 1
+                  config:
+                    securityContext:
+                      readOnlyRootFilesystem: {role_group_read_only_root_filesystem}
+            "#
+        ));
+
+        let statefulset = build_statefulset(&hive, "default");
+        let container = hive_container(&statefulset);
+
+        if !read_only {
+            assert_eq!(container.security_context, None);
+            return;
+        }
+        assert_eq!(
+            container
+                .security_context
+                .as_ref()
+                .and_then(|security_context| security_context.read_only_root_filesystem),
+            Some(true)
+        );
+
+        // All paths written by the container command have to be backed by emptyDir volumes
+        let empty_dirs = statefulset
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.template.spec.as_ref())
+            .map(|pod_spec| pod_spec.volumes.clone().unwrap_or_default())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|volume| volume.empty_dir.is_some())
+            .map(|volume| volume.name)
+            .collect::<Vec<_>>();
+        let writable_mounts = container
+            .volume_mounts
+            .iter()
+            .flatten()
+            .filter(|mount| empty_dirs.contains(&mount.name))
+            .map(|mount| mount.mount_path.as_str())
+            .collect::<Vec<_>>();
+
+        for path in [
+            STACKABLE_CONFIG_DIR,
+            STACKABLE_LOG_DIR,
+            STACKABLE_TRUST_STORE,
+            TMP_DIR,
+        ] {
+            assert!(
+                writable_mounts
+                    .iter()
+                    .any(|mount_path| path.starts_with(mount_path)),
+                "{path} is not writable"
+            );
+        }
+    }
+
+    #[test]
+    fn test_acid_settings_spread_over_role_and_role_group() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                supportConcurrency: true
+                warehouseDir: s3a://bucket/managed
+              roleGroups:
+                default:
+                  replicas: 1
+                  config:
+                    transactionalEventListeners:
+                      - com.example.ReplicationListener
+                    warehouseDirTemplate: s3a://bucket/{type}
+                overridden:
+                  replicas: 1
+                  configOverrides:
+                    hive-site.xml:
+                      hive.txn.manager: com.example.TxnManager
+            "#,
+        );
+
+        let config_map = build_config_map(&hive, "default");
+
+        assert_eq!(
+            hive_site_property(&config_map, "hive.metastore.transactional.event.listeners")
+                .as_deref(),
+            Some(
+                "org.apache.hive.hcatalog.listener.DbNotificationListener,\
+                com.example.ReplicationListener"
+            )
+        );
+        assert_eq!(
+            hive_site_property(&config_map, "hive.txn.manager").as_deref(),
+            Some("org.apache.hadoop.hive.ql.lockmgr.DbTxnManager")
+        );
+        assert_eq!(
+            hive_site_property(&config_map, "hive.metastore.warehouse.dir").as_deref(),
+            Some("s3a://bucket/managed")
+        );
+        assert_eq!(
+            hive_site_property(&config_map, "hive.metastore.warehouse.external.dir").as_deref(),
+            Some("s3a://bucket/external")
+        );
+
+        // The configOverrides still take precedence over the derived properties
+        let config_map = build_config_map(&hive, "overridden");
+
+        assert_eq!(
+            hive_site_property(&config_map, "hive.txn.manager").as_deref(),
+            Some("com.example.TxnManager")
+        );
+    }
+
+    #[test]
+    fn test_templated_warehouse_dir() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                warehouseDir: s3a://bucket/${env:CLUSTER_ENV}/warehouse
+              envOverrides:
+                CLUSTER_ENV: staging
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+
+        let config_map = build_config_map(&hive, "default");
+
+        // The placeholder is replaced by config-utils when the container starts
+        assert_eq!(
+            hive_site_property(&config_map, "hive.metastore.warehouse.dir").as_deref(),
+            Some("s3a://bucket/${env:CLUSTER_ENV}/warehouse")
+        );
+    }
+
+    #[rstest]
+    // Hive 3 and 4 default to different encoders, which must be kept for existing consumers
+    #[case::hive_3_default("3.1.3", None)]
+    #[case::hive_4_default("4.0.0", None)]
+    #[case::hive_4_json("4.0.0", Some(MetaStoreConfig::METASTORE_EVENT_MESSAGE_FACTORY_JSON))]
+    fn test_event_message_factory(#[case] product_version: &str, #[case] factory: Option<&str>) {
+        let mut spec = serde_yaml::from_str::<serde_yaml::Value>(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        )
+        .unwrap();
+        spec["image"]["productVersion"] = product_version.into();
+        if let Some(factory) = factory {
+            spec["metastore"]["config"]["eventMessageFactory"] = factory.into();
+        }
+        let hive = hive_from_yaml(&serde_yaml::to_string(&spec).unwrap());
+
+        let config_map = build_config_map(&hive, "default");
+
+        assert_eq!(
+            hive_site_property(&config_map, "hive.metastore.event.message.factory").as_deref(),
+            factory
+        );
+    }
+
+    #[rstest]
+    #[case::unset("{}", "{}", None)]
+    #[case::role("{limitPartitionRequest: 10000}", "{}", Some("10000"))]
+    #[case::role_group("{}", "{limitPartitionRequest: 500}", Some("500"))]
+    #[case::role_group_precedence(
+        "{limitPartitionRequest: 10000}",
+        "{limitPartitionRequest: 500}",
+        Some("500")
+    )]
+    fn test_limit_partition_request(
+        #[case] role_config: &str,
+        #[case] rolegroup_config: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let hive = hive_from_yaml(&format!(
+            r#"
+            metastore:
+              config: {role_config}
+              roleGroups:
+                default:
+                  replicas: 1
+                  config: {rolegroup_config}
+            "#
+        ));
+
+        let config_map = build_config_map(&hive, "default");
+
+        assert_eq!(
+            hive_site_property(&config_map, "hive.metastore.limit.partition.request").as_deref(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::tls("{tls: {secretClass: tls}}", true)]
+    #[case::tls_and_ldap(
+        "{tls: {secretClass: tls}, ldap: {url: 'ldaps://ldap.example.com:636'}}",
+        true
+    )]
+    #[case::other_authentication("{ldap: {url: 'ldaps://ldap.example.com:636'}}", false)]
+    fn test_tls_authentication(#[case] authentication: &str, #[case] enabled: bool) {
+        let hive = hive_from_yaml(&format!(
+            r#"
+            clusterConfig:
+              authentication: {authentication}
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#
+        ));
+
+        let config_map = build_config_map(&hive, "default");
+        let statefulset = build_statefulset(&hive, "default");
+        let tls_volume = statefulset
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.template.spec.as_ref())
+            .and_then(|pod_spec| pod_spec.volumes.as_ref())
+            .and_then(|volumes| volumes.iter().find(|volume| volume.name == "tls"));
+
+        if !enabled {
+            assert_eq!(
+                hive_site_property(&config_map, "hive.metastore.use.SSL"),
+                None
+            );
+            assert_eq!(tls_volume, None);
+            return;
+        }
+
+        assert_eq!(
+            hive_site_property(&config_map, "hive.metastore.use.SSL").as_deref(),
+            Some("true")
+        );
+        assert_eq!(
+            hive_site_property(&config_map, "hive.metastore.keystore.path").as_deref(),
+            Some("/stackable/tls/keystore.p12")
+        );
+        assert_eq!(
+            hive_site_property(&config_map, "hive.metastore.truststore.path").as_deref(),
+            Some("/stackable/tls/truststore.p12")
+        );
+
+        let annotations = tls_volume
+            .expect("TLS volume is missing")
+            .ephemeral
+            .as_ref()
+            .and_then(|ephemeral| ephemeral.volume_claim_template.as_ref())
+            .and_then(|template| template.metadata.as_ref())
+            .and_then(|metadata| metadata.annotations.as_ref())
+            .expect("TLS volume has no secret annotations");
+        assert_eq!(
+            annotations
+                .get("secrets.stackable.tech/class")
+                .map(String::as_str),
+            Some("tls")
+        );
+        assert_eq!(
+            annotations
+                .get("secrets.stackable.tech/format")
+                .map(String::as_str),
+            Some("tls-pkcs12")
+        );
+        // Clients connect to the role Service, so its name has to be in the certificate
+        assert_eq!(
+            annotations
+                .get("secrets.stackable.tech/scope")
+                .map(String::as_str),
+            Some("pod,service=simple-hive")
+        );
+        assert!(hive_container(&statefulset)
+            .volume_mounts
+            .iter()
+            .flatten()
+            .any(|mount| mount.name == "tls" && mount.mount_path == "/stackable/tls"));
+    }
+
+    #[test]
+    fn test_cluster_domain_override() {
+        let hive = hive_from_yaml(
+            r#"
+            clusterConfig:
+              clusterDomain: corp.example
+              authentication:
+                kerberos:
+                  secretClass: kerberos
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+        let operator_cluster_info = KubernetesClusterInfo {
+            cluster_domain: DomainName::try_from("cluster.local").unwrap(),
+        };
+
+        let cluster_info = cluster_info(&hive, &operator_cluster_info).unwrap();
+
+        assert_eq!(
+            hive.pods().unwrap().next().unwrap().fqdn(&cluster_info),
+            "simple-hive-metastore-default-0.simple-hive-metastore-default.default.svc.corp.example"
+        );
+        assert_eq!(
+            hive_site_property(
+                &build_config_map(&hive, "default"),
+                "hive.metastore.kerberos.principal"
+            )
+            .as_deref(),
+            Some("hive/simple-hive.default.svc.corp.example@${env.KERBEROS_REALM}")
+        );
+    }
+
+    #[test]
+    fn test_invalid_cluster_domain() {
+        let hive = hive_from_yaml("clusterConfig: { clusterDomain: not_a_domain }");
+        let operator_cluster_info = KubernetesClusterInfo {
+            cluster_domain: DomainName::try_from("cluster.local").unwrap(),
+        };
+
+        assert!(matches!(
+            cluster_info(&hive, &operator_cluster_info),
+            Err(Error::InvalidClusterDomain { .. })
+        ));
+    }
+
+    #[rstest]
+    #[case::role("hive-jmx", "null", Some("hive-jmx"), Some("hive-jmx"))]
+    #[case::role_group("null", "hive-jmx", Some("hive-jmx"), None)]
+    #[case::role_group_overrides_role(
+        "hive-jmx",
+        "hive-jmx-debug",
+        Some("hive-jmx-debug"),
+        Some("hive-jmx")
+    )]
+    #[case::default("null", "null", None, None)]
+    fn test_jmx_remote(
+        #[case] secret: &str,
+        #[case] role_group_secret: &str,
+        #[case] expected_default: Option<&str>,
+        #[case] expected_other: Option<&str>,
+    ) {
+        let hive = hive_from_yaml(&format!(
+            r#"
+            metastore:
+              config:
+                jmxRemoteCredentialsSecret: {secret}
+              roleGroups:
+                default:
+                  replicas: 1
+                  config:
+                    jmxRemoteCredentialsSecret: {role_group_secret}
+                other:
+                  replicas: 1
+            "#
+        ));
+
+        for (rolegroup_name, expected_secret) in
+            [("default", expected_default), ("other", expected_other)]
+        {
+            let statefulset = build_statefulset(&hive, rolegroup_name);
+            let container = hive_container(&statefulset);
+            let hadoop_opts = container
+                .env
+                .iter()
+                .flatten()
+                .find(|env_var| env_var.name == "HADOOP_OPTS")
+                .and_then(|env_var| env_var.value.as_deref())
+                .expect("HADOOP_OPTS env var must exist");
+            let has_jmx_port = container
+                .ports
+                .iter()
+                .flatten()
+                .any(|port| port.name.as_deref() == Some("jmx") && port.container_port == 9085);
+
+            let Some(expected_secret) = expected_secret else {
+                assert!(!hadoop_opts.contains("jmxremote"), "{rolegroup_name}");
+                assert!(!has_jmx_port, "{rolegroup_name}");
+                continue;
+            };
+            assert!(hadoop_opts.contains("-Dcom.sun.management.jmxremote.port=9085"));
+            assert!(hadoop_opts.contains("-Dcom.sun.management.jmxremote.authenticate=true"));
+            assert!(has_jmx_port, "{rolegroup_name}");
+            assert!(container.env.iter().flatten().any(|env_var| *env_var
+                == env_var_from_secret(JMX_REMOTE_PASSWORD_ENV, expected_secret, "password")));
+        }
+    }
+
+    #[rstest]
+    #[case::memory(Some("Memory"), Some("Memory"))]
+    #[case::disk(Some("Disk"), None)]
+    #[case::default(None, None)]
+    fn test_log_volume_medium(#[case] medium: Option<&str>, #[case] expected: Option<&str>) {
+        let mut spec = serde_yaml::from_str::<serde_yaml::Value>(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        )
+        .unwrap();
+        if let Some(medium) = medium {
+            spec["metastore"]["config"]["logVolume"]["medium"] = medium.into();
+        }
+        let hive = hive_from_yaml(&serde_yaml::to_string(&spec).unwrap());
+
+        let statefulset = build_statefulset(&hive, "default");
+        let log_volume = statefulset
+            .spec
+            .and_then(|spec| spec.template.spec)
+            .and_then(|pod_spec| pod_spec.volumes)
+            .and_then(|volumes| {
+                volumes
+                    .into_iter()
+                    .find(|volume| volume.name == STACKABLE_LOG_DIR_NAME)
+            })
+            .expect("log volume must exist");
+
+        assert_eq!(
+            log_volume
+                .empty_dir
+                .and_then(|empty_dir| empty_dir.medium)
+                .as_deref(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::mebibytes("100Mi", Some(100.0), "50MB")]
+    #[case::gibibytes("1Gi", Some(1024.0), "512MB")]
+    #[case::default("null", Some(10.0), "5MB")]
+    #[case::invalid("lots", None, "")]
+    fn test_log_volume_max_files_size(
+        #[case] max_files_size: &str,
+        #[case] expected_mebibytes: Option<f32>,
+        #[case] expected_file_size: &str,
+    ) {
+        let hive = hive_from_yaml(&format!(
+            r#"
+            metastore:
+              config:
+                logVolume:
+                  maxFilesSize: {max_files_size}
+              roleGroups:
+                default:
+                  replicas: 1
+            "#
+        ));
+        let merged_config = hive
+            .merged_config(
+                &HiveRole::MetaStore,
+                &hive.metastore_rolegroup_ref("default"),
+            )
+            .unwrap();
+
+        let Some(expected_mebibytes) = expected_mebibytes else {
+            assert!(matches!(
+                max_log_files_size(&merged_config),
+                Err(Error::InvalidLogFilesSize { .. })
+            ));
+            return;
+        };
+        let expected_max_files_size = MemoryQuantity {
+            value: expected_mebibytes,
+            unit: BinaryMultiple::Mebi,
+        };
+        assert_eq!(
+            max_log_files_size(&merged_config).unwrap(),
+            expected_max_files_size
+        );
+
+        let statefulset = build_statefulset(&hive, "default");
+        let log_volume = statefulset
+            .spec
+            .and_then(|spec| spec.template.spec)
+            .and_then(|pod_spec| pod_spec.volumes)
+            .and_then(|volumes| {
+                volumes
+                    .into_iter()
+                    .find(|volume| volume.name == STACKABLE_LOG_DIR_NAME)
+            })
+            .expect("log volume must exist");
+        assert_eq!(
+            log_volume
+                .empty_dir
+                .and_then(|empty_dir| empty_dir.size_limit),
+            Some(product_logging::framework::calculate_log_volume_size_limit(
+                &[expected_max_files_size]
+            ))
+        );
+
+        // The size is split between the current and the rolled over log file
+        let config_map = build_config_map(&hive, "default");
+        let log4j2_properties =
+            &config_map.data.as_ref().unwrap()[HIVE_METASTORE_LOG4J2_PROPERTIES];
+        assert!(log4j2_properties.contains(&format!(
+            "appender.FILE.policies.size.size = {expected_file_size}"
+        )));
+    }
+
+    #[rstest]
+    #[case::when_scaled("{whenScaled: Delete}", "{}", Some((Some("Delete"), None)))]
+    #[case::spread_over_levels(
+        "{whenScaled: Delete}",
+        "{whenDeleted: Retain}",
+        Some((Some("Delete"), Some("Retain")))
+    )]
+    #[case::role_group_overrides_role(
+        "{whenScaled: Delete}",
+        "{whenScaled: Retain}",
+        Some((Some("Retain"), None))
+    )]
+    #[case::default("{}", "{}", None)]
+    fn test_volume_claim_retention_policy(
+        #[case] retention: &str,
+        #[case] role_group_retention: &str,
+        #[case] expected: Option<(Option<&str>, Option<&str>)>,
+    ) {
+        let hive = hive_from_yaml(&format!(
+            r#"
+            metastore:
+              config:
+                resources:
+                  storage:
+                    additionalVolumes:
+                      scratch:
+                        mountPath: /stackable/scratch
+                        capacity: 1Gi
+                    volumeClaimRetention: {retention}
+              roleGroups:
+                default:
+                  replicas: 1
+                  config:
+                    resources:
+                      storage:
+                        volumeClaimRetention: {role_group_retention}
+            "#
+        ));
+
+        let statefulset = build_statefulset(&hive, "default");
+
+        assert_eq!(
+            statefulset
+                .spec
+                .and_then(|spec| spec.persistent_volume_claim_retention_policy),
+            expected.map(|(when_scaled, when_deleted)| {
+                StatefulSetPersistentVolumeClaimRetentionPolicy {
+                    when_scaled: when_scaled.map(str::to_string),
+                    when_deleted: when_deleted.map(str::to_string),
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_replicas_with_autoscaling() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 2
+                  config:
+                    autoscaling:
+                      maxReplicas: 4
+                other:
+                  replicas: 2
+            "#,
+        );
+
+        assert_eq!(
+            build_statefulset(&hive, "default")
+                .spec
+                .and_then(|spec| spec.replicas),
+            None
+        );
+        assert_eq!(
+            build_statefulset(&hive, "other")
+                .spec
+                .and_then(|spec| spec.replicas),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_automount_service_account_token() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+                  config:
+                    automountServiceAccountToken: false
+                other:
+                  replicas: 1
+            "#,
+        );
+
+        let automount_service_account_token = |rolegroup_name| {
+            build_statefulset(&hive, rolegroup_name)
+                .spec
+                .and_then(|spec| spec.template.spec)
+                .and_then(|pod_spec| pod_spec.automount_service_account_token)
+        };
+
+        assert_eq!(automount_service_account_token("default"), Some(false));
+        assert_eq!(automount_service_account_token("other"), None);
+    }
+
+    #[test]
+    fn test_min_ready_seconds() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                minReadySeconds: 10
+              roleGroups:
+                default:
+                  replicas: 1
+                  config:
+                    minReadySeconds: 30
+                other:
+                  replicas: 1
+            "#,
+        );
+
+        assert_eq!(
+            build_statefulset(&hive, "default")
+                .spec
+                .and_then(|spec| spec.min_ready_seconds),
+            Some(30)
+        );
+        assert_eq!(
+            build_statefulset(&hive, "other")
+                .spec
+                .and_then(|spec| spec.min_ready_seconds),
+            Some(10)
+        );
+    }
+
+    #[rstest]
+    #[case::rolling_update_with_partition(
+        "{type: RollingUpdate, partition: 2}",
+        Some(StatefulSetUpdateStrategy {
+            type_: Some("RollingUpdate".to_string()),
+            rolling_update: Some(RollingUpdateStatefulSetStrategy {
+                partition: Some(2),
+                ..RollingUpdateStatefulSetStrategy::default()
+            }),
+        })
+    )]
+    #[case::on_delete(
+        "{type: OnDelete}",
+        Some(StatefulSetUpdateStrategy {
+            type_: Some("OnDelete".to_string()),
+            rolling_update: None,
+        })
+    )]
+    #[case::on_delete_ignores_partition(
+        "{type: OnDelete, partition: 2}",
+        Some(StatefulSetUpdateStrategy {
+            type_: Some("OnDelete".to_string()),
+            rolling_update: None,
+        })
+    )]
+    #[case::default("null", None)]
+    fn test_update_strategy(
+        #[case] update_strategy: &str,
+        #[case] expected: Option<StatefulSetUpdateStrategy>,
+    ) {
+        let mut spec = serde_yaml::from_str::<serde_yaml::Value>(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 3
+            "#,
+        )
+        .unwrap();
+        spec["metastore"]["config"]["updateStrategy"] =
+            serde_yaml::from_str(update_strategy).unwrap();
+        let hive = hive_from_yaml(&serde_yaml::to_string(&spec).unwrap());
+
+        let statefulset = build_statefulset(&hive, "default");
+
+        assert_eq!(
+            statefulset.spec.and_then(|spec| spec.update_strategy),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::ordered_ready(Some("OrderedReady"), "OrderedReady")]
+    #[case::parallel(Some("Parallel"), "Parallel")]
+    #[case::default(None, "Parallel")]
+    fn test_pod_management_policy(#[case] policy: Option<&str>, #[case] expected: &str) {
+        let mut spec = serde_yaml::from_str::<serde_yaml::Value>(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        )
+        .unwrap();
+        if let Some(policy) = policy {
+            spec["metastore"]["config"]["podManagementPolicy"] = policy.into();
+        }
+        let hive = hive_from_yaml(&serde_yaml::to_string(&spec).unwrap());
+
+        let statefulset = build_statefulset(&hive, "default");
+
+        assert_eq!(
+            statefulset
+                .spec
+                .and_then(|spec| spec.pod_management_policy)
+                .as_deref(),
+            Some(expected)
+        );
+    }
+
+    #[rstest]
+    #[case::file(Some("File"), None, Some("File"), None)]
+    #[case::fallback_to_logs(
+        Some("FallbackToLogsOnError"),
+        None,
+        Some("FallbackToLogsOnError"),
+        None
+    )]
+    #[case::custom_path(
+        None,
+        Some("/stackable/log/termination-log"),
+        Some("FallbackToLogsOnError"),
+        Some("/stackable/log/termination-log")
+    )]
+    #[case::default(None, None, Some("FallbackToLogsOnError"), None)]
+    fn test_termination_message(
+        #[case] policy: Option<&str>,
+        #[case] path: Option<&str>,
+        #[case] expected_policy: Option<&str>,
+        #[case] expected_path: Option<&str>,
+    ) {
+        let mut spec = serde_yaml::from_str::<serde_yaml::Value>(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        )
+        .unwrap();
+        if let Some(policy) = policy {
+            spec["metastore"]["config"]["terminationMessagePolicy"] = policy.into();
+        }
+        if let Some(path) = path {
+            spec["metastore"]["config"]["terminationMessagePath"] = path.into();
+        }
+        let hive = hive_from_yaml(&serde_yaml::to_string(&spec).unwrap());
+
+        let statefulset = build_statefulset(&hive, "default");
+        let container = hive_container(&statefulset);
+
+        assert_eq!(
+            container.termination_message_policy.as_deref(),
+            expected_policy
+        );
+        assert_eq!(container.termination_message_path.as_deref(), expected_path);
+    }
+}
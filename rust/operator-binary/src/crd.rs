@@ -0,0 +1,83 @@
+//! Prints the HiveCluster CRD, optionally restricted to a single version
+use snafu::{ensure, ResultExt, Snafu};
+use stackable_hive_crd::HiveCluster;
+use stackable_operator::{
+    k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
+    CustomResourceExt,
+};
+
+#[derive(Snafu, Debug)]
+pub enum Error {
+    #[snafu(display("failed to generate the CRD"))]
+    GenerateCrd {
+        source: stackable_operator::error::Error,
+    },
+
+    #[snafu(display("failed to parse the generated CRD"))]
+    ParseCrd { source: serde_yaml::Error },
+
+    #[snafu(display("failed to serialize the CRD"))]
+    SerializeCrd { source: serde_yaml::Error },
+
+    #[snafu(display("the CRD has no version {version:?}, available versions are {available:?}"))]
+    UnknownCrdVersion {
+        version: String,
+        available: Vec<String>,
+    },
+}
+
+/// The CRD as YAML. If a `version` is given, all other versions are removed, e.g. to pin the
+/// stored version in GitOps repositories.
+pub fn crd_yaml(operator_version: &str, version: Option<&str>) -> Result<String, Error> {
+    let yaml = HiveCluster::yaml_schema(operator_version).context(GenerateCrdSnafu)?;
+    let Some(version) = version else {
+        return Ok(yaml);
+    };
+
+    let mut crd: CustomResourceDefinition = serde_yaml::from_str(&yaml).context(ParseCrdSnafu)?;
+    let available = crd
+        .spec
+        .versions
+        .iter()
+        .map(|crd_version| crd_version.name.clone())
+        .collect::<Vec<_>>();
+    ensure!(
+        available.iter().any(|name| name == version),
+        UnknownCrdVersionSnafu { version, available }
+    );
+    crd.spec
+        .versions
+        .retain(|crd_version| crd_version.name == version);
+
+    serde_yaml::to_string(&crd).context(SerializeCrdSnafu)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crd_version() {
+        let crd: CustomResourceDefinition = serde_yaml::from_str(
+            &crd_yaml(crate::built_info::PKG_VERSION, Some("v1alpha1")).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            crd.spec
+                .versions
+                .iter()
+                .map(|crd_version| crd_version.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["v1alpha1"]
+        );
+        assert!(!serde_yaml::to_string(&crd)
+            .unwrap()
+            .contains("DOCS_BASE_URL_PLACEHOLDER"));
+
+        assert!(matches!(
+            crd_yaml(crate::built_info::PKG_VERSION, Some("v1beta1")),
+            Err(Error::UnknownCrdVersion { .. })
+        ));
+    }
+}
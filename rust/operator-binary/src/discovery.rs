@@ -8,6 +8,7 @@ use stackable_operator::{
     k8s_openapi::api::core::v1::ConfigMap,
     k8s_openapi::api::core::v1::{Endpoints, Service, ServiceSpec},
     kube::{runtime::reflector::ObjectRef, Resource},
+    utils::cluster_info::KubernetesClusterInfo,
 };
 use std::collections::BTreeSet;
 use std::num::TryFromIntError;
@@ -64,28 +65,19 @@ pub async fn build_discovery_configmaps(
     resolved_product_image: &ResolvedProductImage,
     svc: &Service,
     chroot: Option<&str>,
+    cluster_info: &KubernetesClusterInfo,
 ) -> Result<Vec<ConfigMap>, Error> {
     let name = owner
         .meta()
         .name
         .as_ref()
         .context(InvalidOwnerNameForDiscoveryConfigMapSnafu)?;
-    let namespace = owner
-        .meta()
-        .namespace
-        .as_deref()
-        .context(NoNamespaceSnafu)?;
-    let cluster_domain = &client.kubernetes_cluster_info.cluster_domain;
-    let mut discovery_configmaps = vec![build_discovery_configmap(
-        name,
+    let mut discovery_configmaps = vec![build_role_discovery_configmap(
         owner,
         hive,
         resolved_product_image,
         chroot,
-        vec![(
-            format!("{name}.{namespace}.svc.{cluster_domain}"),
-            HIVE_PORT,
-        )],
+        cluster_info,
     )?];
 
     // TODO: Temporary solution until listener-operator is finished
@@ -109,6 +101,40 @@ pub async fn build_discovery_configmaps(
     Ok(discovery_configmaps)
 }
 
+/// Builds the discovery [`ConfigMap`] pointing to the role [`Service`], which load-balances across
+/// the Pods of all metastore role groups.
+fn build_role_discovery_configmap(
+    owner: &impl Resource<DynamicType = ()>,
+    hive: &HiveCluster,
+    resolved_product_image: &ResolvedProductImage,
+    chroot: Option<&str>,
+    cluster_info: &KubernetesClusterInfo,
+) -> Result<ConfigMap, Error> {
+    let name = owner
+        .meta()
+        .name
+        .as_ref()
+        .context(InvalidOwnerNameForDiscoveryConfigMapSnafu)?;
+    let namespace = owner
+        .meta()
+        .namespace
+        .as_deref()
+        .context(NoNamespaceSnafu)?;
+    let cluster_domain = &cluster_info.cluster_domain;
+
+    build_discovery_configmap(
+        name,
+        owner,
+        hive,
+        resolved_product_image,
+        chroot,
+        vec![(
+            format!("{name}.{namespace}.svc.{cluster_domain}"),
+            HIVE_PORT,
+        )],
+    )
+}
+
 /// Build a discovery [`ConfigMap`] containing information about how to connect to a certain [`HiveCluster`]
 ///
 /// `hosts` will usually come from the cluster role service or [`nodeport_hosts`].
@@ -205,3 +231,67 @@ async fn nodeport_hosts(
         .collect::<Result<BTreeSet<_>, _>>()?;
     Ok(addrs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use stackable_hive_crd::test_util::hive_from_yaml;
+    use stackable_operator::commons::networking::DomainName;
+
+    use crate::controller::{build_metastore_role_service, DOCKER_IMAGE_BASE_NAME};
+
+    #[test]
+    fn test_discovery_with_multiple_rolegroups() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 2
+                secondary:
+                  replicas: 1
+            "#,
+        );
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+        let cluster_info = KubernetesClusterInfo {
+            cluster_domain: DomainName::try_from("cluster.local").unwrap(),
+        };
+
+        // The role Service the discovery ConfigMap points to selects the Pods of all role groups
+        let role_service = build_metastore_role_service(&hive, &resolved_product_image).unwrap();
+        let selector = role_service.spec.unwrap().selector.unwrap();
+        assert_eq!(
+            selector
+                .get("app.kubernetes.io/component")
+                .map(String::as_str),
+            Some("metastore")
+        );
+        assert!(!selector.contains_key("app.kubernetes.io/role-group"));
+
+        let discovery_configmap = build_role_discovery_configmap(
+            &hive,
+            &hive,
+            &resolved_product_image,
+            None,
+            &cluster_info,
+        )
+        .unwrap();
+
+        assert_eq!(
+            discovery_configmap.metadata.name.as_deref(),
+            Some("simple-hive")
+        );
+        assert_eq!(
+            discovery_configmap
+                .data
+                .unwrap()
+                .get("HIVE")
+                .map(String::as_str),
+            Some("thrift://simple-hive.default.svc.cluster.local:9083")
+        );
+    }
+}
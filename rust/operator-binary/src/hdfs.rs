@@ -0,0 +1,80 @@
+//! Checks the warehouse directories against the HDFS cluster of the discovery ConfigMap
+
+/// The nameservices of the HDFS cluster in the `hdfs-site.xml`
+const DFS_NAMESERVICES: &str = "dfs.nameservices";
+
+/// A warning if the warehouse directory is located on an HDFS nameservice which is not provided by
+/// the `hdfs-site.xml` of the HDFS discovery ConfigMap. The metastore can not resolve such a
+/// nameservice and fails to create databases below the warehouse directory.
+pub fn warehouse_nameservice_warning(warehouse_dir: &str, hdfs_site: &str) -> Option<String> {
+    let authority = warehouse_dir
+        .strip_prefix("hdfs://")?
+        .split('/')
+        .next()
+        .filter(|authority| !authority.is_empty())?;
+    // An authority with a port addresses a NameNode directly instead of a nameservice
+    if authority.contains(':') {
+        return None;
+    }
+
+    let nameservices = hadoop_xml_property(hdfs_site, DFS_NAMESERVICES).unwrap_or_default();
+    if nameservices
+        .split(',')
+        .any(|nameservice| nameservice.trim() == authority)
+    {
+        return None;
+    }
+
+    Some(format!(
+        "The warehouse directory {warehouse_dir:?} is located on the HDFS nameservice \
+        {authority:?}, but the HDFS discovery ConfigMap only provides the nameservices \
+        [{nameservices}]. Please check clusterConfig.hdfs.configMap and the warehouse directory."
+    ))
+}
+
+/// The value of the property `name` in a Hadoop XML config file
+fn hadoop_xml_property(xml: &str, name: &str) -> Option<String> {
+    let (_, after_name) = xml.split_once(&format!("<name>{name}</name>"))?;
+    let (_, after_value_start) = after_name.split_once("<value>")?;
+    let (value, _) = after_value_start.split_once("</value>")?;
+
+    Some(value.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::rstest;
+
+    const HDFS_SITE: &str = r#"<?xml version="1.0"?>
+<configuration>
+  <property>
+    <name>dfs.ha.namenodes.hdfs</name>
+    <value>name-0,name-1</value>
+  </property>
+  <property>
+    <name>dfs.nameservices</name>
+    <value>hdfs</value>
+  </property>
+</configuration>"#;
+
+    #[rstest]
+    #[case::matching("hdfs://hdfs/user/hive/warehouse", false)]
+    #[case::mismatching("hdfs://other-hdfs/user/hive/warehouse", true)]
+    #[case::default_fs("hdfs:///user/hive/warehouse", false)]
+    #[case::namenode_address("hdfs://namenode:8020/user/hive/warehouse", false)]
+    #[case::s3("s3a://warehouse/hive", false)]
+    fn test_warehouse_nameservice_warning(#[case] warehouse_dir: &str, #[case] warns: bool) {
+        let warning = warehouse_nameservice_warning(warehouse_dir, HDFS_SITE);
+
+        assert_eq!(warning.is_some(), warns, "unexpected warning {warning:?}");
+        if let Some(warning) = warning {
+            assert!(
+                warning.contains("\"other-hdfs\""),
+                "unexpected warning {warning}"
+            );
+            assert!(warning.contains("[hdfs]"), "unexpected warning {warning}");
+        }
+    }
+}
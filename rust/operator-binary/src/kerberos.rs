@@ -1,6 +1,7 @@
-use indoc::formatdoc;
 use snafu::{ResultExt, Snafu};
-use stackable_hive_crd::{HiveCluster, HiveRole, HIVE_SITE_XML, STACKABLE_CONFIG_DIR};
+use stackable_hive_crd::{
+    HiveCluster, HiveRole, MetaStoreConfig, HIVE_SITE_XML, STACKABLE_CONFIG_DIR,
+};
 use stackable_operator::{
     builder::{
         self,
@@ -65,6 +66,8 @@ pub fn add_kerberos_pod_config(
     Ok(())
 }
 
+/// The `hive-site.xml` properties needed for Kerberos. SASL is enabled by default, but can be
+/// disabled with the `saslEnabled` setting of the metastore.
 pub fn kerberos_config_properties(
     hive: &HiveCluster,
     hive_namespace: &str,
@@ -100,30 +103,87 @@ pub fn kerberos_config_properties(
             "/stackable/kerberos/keytab".to_string(),
         ),
         (
-            "hive.metastore.sasl.enabled".to_string(),
+            MetaStoreConfig::METASTORE_SASL_ENABLED.to_string(),
             "true".to_string(),
         ),
     ])
 }
 
+/// Escapes the characters that have a special meaning in the replacement of a `sed` substitution
+/// (`\`, `/` and `&`), so that the realm is always inserted literally.
+const ESCAPE_KERBEROS_REALM_COMMAND: &str =
+    r#"export KERBEROS_REALM_ESCAPED=$(printf '%s' "$KERBEROS_REALM" | sed -e 's/[\/&]/\\&/g')"#;
+
 pub fn kerberos_container_start_commands(hive: &HiveCluster) -> String {
     if !hive.has_kerberos_enabled() {
         return String::new();
     }
 
-    let mut args = vec![formatdoc! {"
-        export KERBEROS_REALM=$(grep -oP 'default_realm = \\K.*' /stackable/kerberos/krb5.conf)
-        sed -i -e 's/${{env.KERBEROS_REALM}}/'\"$KERBEROS_REALM/g\" {STACKABLE_CONFIG_DIR}/{HIVE_SITE_XML}",
-    }];
+    let mut args = vec![
+        "export KERBEROS_REALM=$(grep -oP 'default_realm = \\K.*' /stackable/kerberos/krb5.conf)"
+            .to_string(),
+        ESCAPE_KERBEROS_REALM_COMMAND.to_string(),
+        replace_kerberos_realm_command(&format!("{STACKABLE_CONFIG_DIR}/{HIVE_SITE_XML}")),
+    ];
 
     if hive.spec.cluster_config.hdfs.is_some() {
         args.extend([
-            formatdoc! {"
-                sed -i -e 's/${{env.KERBEROS_REALM}}/'\"$KERBEROS_REALM/g\" {STACKABLE_CONFIG_DIR}/core-site.xml
-                sed -i -e 's/${{env.KERBEROS_REALM}}/'\"$KERBEROS_REALM/g\" {STACKABLE_CONFIG_DIR}/hdfs-site.xml",
-            }
+            replace_kerberos_realm_command(&format!("{STACKABLE_CONFIG_DIR}/core-site.xml")),
+            replace_kerberos_realm_command(&format!("{STACKABLE_CONFIG_DIR}/hdfs-site.xml")),
         ]);
     }
 
     args.join("\n")
 }
+
+/// Replaces the `${env.KERBEROS_REALM}` placeholder in the given file with the escaped realm.
+/// Only this exact placeholder is matched, all other `${...}` sequences are left untouched.
+fn replace_kerberos_realm_command(file: &str) -> String {
+    format!(r#"sed -i -e 's/\${{env\.KERBEROS_REALM}}/'"$KERBEROS_REALM_ESCAPED"'/g' {file}"#)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, process::Command};
+
+    #[test]
+    fn test_replace_kerberos_realm_preserves_other_placeholders() {
+        let file = std::env::temp_dir().join(format!(
+            "hive-site-kerberos-realm-{pid}.xml",
+            pid = std::process::id()
+        ));
+        fs::write(
+            &file,
+            "hive/simple-hive.default.svc.cluster.local@${env.KERBEROS_REALM}\n\
+             ${env:SOME_VAR}\n\
+             ${envXKERBEROS_REALM}\n\
+             ${file:UTF-8:/stackable/secret/password}\n",
+        )
+        .unwrap();
+
+        let status = Command::new("bash")
+            .arg("-euo")
+            .arg("pipefail")
+            .arg("-c")
+            .arg(format!(
+                "{ESCAPE_KERBEROS_REALM_COMMAND}\n{replace}",
+                replace = replace_kerberos_realm_command(file.to_str().unwrap())
+            ))
+            .env("KERBEROS_REALM", "EXAMPLE.COM/&")
+            .status()
+            .unwrap();
+        let content = fs::read_to_string(&file).unwrap();
+        fs::remove_file(&file).unwrap();
+
+        assert!(status.success());
+        assert_eq!(
+            content,
+            "hive/simple-hive.default.svc.cluster.local@EXAMPLE.COM/&\n\
+             ${env:SOME_VAR}\n\
+             ${envXKERBEROS_REALM}\n\
+             ${file:UTF-8:/stackable/secret/password}\n"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! LDAP authentication of metastore clients
+use stackable_hive_crd::HiveCluster;
+use std::collections::BTreeMap;
+
+/// The `hive-site.xml` properties needed for LDAP authentication, including the optional group
+/// filtering
+pub fn ldap_config_properties(hive: &HiveCluster) -> BTreeMap<String, String> {
+    let Some(ldap) = hive.ldap_authentication() else {
+        return BTreeMap::new();
+    };
+
+    let mut properties = BTreeMap::from([
+        (
+            "hive.metastore.authentication".to_string(),
+            "LDAP".to_string(),
+        ),
+        (
+            "hive.metastore.authentication.ldap.url".to_string(),
+            ldap.url.clone(),
+        ),
+    ]);
+
+    if let Some(base_dn) = &ldap.base_dn {
+        properties.insert(
+            "hive.metastore.authentication.ldap.baseDN".to_string(),
+            base_dn.clone(),
+        );
+    }
+    if !ldap.group_filter.is_empty() {
+        properties.insert(
+            "hive.metastore.authentication.ldap.groupFilter".to_string(),
+            ldap.group_filter.join(","),
+        );
+    }
+    if !ldap.group_dn_pattern.is_empty() {
+        // Hive separates the patterns with colons, as DNs contain commas
+        properties.insert(
+            "hive.metastore.authentication.ldap.groupDNPattern".to_string(),
+            ldap.group_dn_pattern.join(":"),
+        );
+    }
+    if let Some(group_membership_key) = &ldap.group_membership_key {
+        properties.insert(
+            "hive.metastore.authentication.ldap.groupMembershipKey".to_string(),
+            group_membership_key.clone(),
+        );
+    }
+    if let Some(group_class_key) = &ldap.group_class_key {
+        properties.insert(
+            "hive.metastore.authentication.ldap.groupClassKey".to_string(),
+            group_class_key.clone(),
+        );
+    }
+
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::rstest;
+    use stackable_hive_crd::test_util::hive_from_yaml;
+
+    #[rstest]
+    #[case::groups(
+        Some(
+            r#"
+            ldap:
+              url: ldaps://ldap.example.com:636
+              baseDn: ou=users,dc=example,dc=com
+              groupFilter:
+                - hive-users
+                - hive-admins
+              groupDnPattern:
+                - cn=%s,ou=groups,dc=example,dc=com
+                - cn=%s,ou=teams,dc=example,dc=com
+              groupMembershipKey: member
+              groupClassKey: groupOfNames
+            "#
+        ),
+        &[
+            ("hive.metastore.authentication", "LDAP"),
+            ("hive.metastore.authentication.ldap.url", "ldaps://ldap.example.com:636"),
+            ("hive.metastore.authentication.ldap.baseDN", "ou=users,dc=example,dc=com"),
+            ("hive.metastore.authentication.ldap.groupFilter", "hive-users,hive-admins"),
+            // The DN patterns contain commas themselves
+            (
+                "hive.metastore.authentication.ldap.groupDNPattern",
+                "cn=%s,ou=groups,dc=example,dc=com:cn=%s,ou=teams,dc=example,dc=com",
+            ),
+            ("hive.metastore.authentication.ldap.groupMembershipKey", "member"),
+            ("hive.metastore.authentication.ldap.groupClassKey", "groupOfNames"),
+        ]
+    )]
+    #[case::empty_groups(
+        Some("ldap: {url: 'ldap://ldap:389', groupFilter: [], groupDnPattern: []}"),
+        &[
+            ("hive.metastore.authentication", "LDAP"),
+            ("hive.metastore.authentication.ldap.url", "ldap://ldap:389"),
+        ]
+    )]
+    #[case::no_ldap(None, &[])]
+    fn test_ldap_config_properties(
+        #[case] authentication: Option<&str>,
+        #[case] expected: &[(&str, &str)],
+    ) {
+        let mut hive = hive_from_yaml("metastore: {roleGroups: {default: {replicas: 1}}}");
+        hive.spec.cluster_config.authentication = authentication.map(|authentication| {
+            serde_yaml::from_str(authentication).expect("illegal test authentication")
+        });
+
+        assert_eq!(
+            ldap_config_properties(&hive),
+            expected
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        );
+    }
+}
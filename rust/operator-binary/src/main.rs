@@ -1,10 +1,21 @@
+mod autoscaling;
+mod cluster_domain;
 mod command;
+mod config;
+mod config_overrides;
 mod controller;
+mod crd;
 mod discovery;
-
+mod hdfs;
 mod kerberos;
+mod ldap;
+mod network_policy;
 mod operations;
 mod product_logging;
+mod s3;
+mod service_monitor;
+mod tls;
+mod zookeeper;
 
 use crate::controller::HIVE_CONTROLLER_NAME;
 
@@ -12,7 +23,7 @@ use clap::{crate_description, crate_version, Parser};
 use futures::stream::StreamExt;
 use stackable_hive_crd::{HiveCluster, APP_NAME};
 use stackable_operator::{
-    cli::{Command, ProductOperatorRun},
+    cli::ProductOperatorRun,
     k8s_openapi::api::{
         apps::v1::StatefulSet,
         core::v1::{ConfigMap, Service},
@@ -20,7 +31,7 @@ use stackable_operator::{
     kube::core::DeserializeGuard,
     kube::runtime::{watcher, Controller},
     logging::controller::report_controller_reconciled,
-    CustomResourceExt,
+    time::Duration,
 };
 use std::sync::Arc;
 
@@ -37,16 +48,54 @@ struct Opts {
     cmd: Command,
 }
 
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Print CRD objects
+    Crd(CrdArgs),
+    /// Run operator
+    Run(HiveRun),
+}
+
+#[derive(clap::Parser)]
+struct CrdArgs {
+    /// Only prints the given version of the CRD, e.g. `v1alpha1`. Prints all versions if not set.
+    #[clap(long)]
+    crd_version: Option<String>,
+}
+
+#[derive(clap::Parser)]
+struct HiveRun {
+    /// Builds all resources, but only logs them instead of applying them to the cluster
+    #[clap(long, env)]
+    dry_run: bool,
+
+    /// Additionally reconciles the HiveClusters in the given interval, e.g. `10m`, to correct drift
+    /// of the managed resources. By default, HiveClusters are only reconciled on changes.
+    #[clap(long, env)]
+    reconcile_interval: Option<Duration>,
+
+    #[clap(flatten)]
+    common: ProductOperatorRun,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let opts = Opts::parse();
     match opts.cmd {
-        Command::Crd => HiveCluster::print_yaml_schema(built_info::PKG_VERSION)?,
-        Command::Run(ProductOperatorRun {
-            product_config,
-            watch_namespace,
-            tracing_target,
-            cluster_info_opts,
+        Command::Crd(CrdArgs { crd_version }) => print!(
+            "{}",
+            crd::crd_yaml(built_info::PKG_VERSION, crd_version.as_deref())?
+        ),
+        Command::Run(HiveRun {
+            dry_run,
+            reconcile_interval,
+            common:
+                ProductOperatorRun {
+                    product_config,
+                    watch_namespace,
+                    tracing_target,
+                    cluster_info_opts,
+                },
         }) => {
             stackable_operator::logging::initialize_logging(
                 "HIVE_OPERATOR_LOG",
@@ -61,6 +110,9 @@ async fn main() -> anyhow::Result<()> {
                 built_info::BUILT_TIME_UTC,
                 built_info::RUSTC_VERSION,
             );
+            if dry_run {
+                tracing::info!("Running in dry-run mode, no resources are applied to the cluster");
+            }
 
             let product_config = product_config.load(&[
                 "deploy/config-spec/properties.yaml",
@@ -73,6 +125,9 @@ async fn main() -> anyhow::Result<()> {
             )
             .await?;
 
+            cluster_domain::check_cluster_domain(&client, &watch_namespace, &cluster_info_opts)
+                .await;
+
             Controller::new(
                 watch_namespace.get_api::<DeserializeGuard<HiveCluster>>(&client),
                 watcher::Config::default(),
@@ -96,6 +151,8 @@ async fn main() -> anyhow::Result<()> {
                 Arc::new(controller::Ctx {
                     client: client.clone(),
                     product_config,
+                    dry_run,
+                    reconcile_interval,
                 }),
             )
             .map(|res| {
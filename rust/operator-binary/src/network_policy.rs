@@ -0,0 +1,278 @@
+//! Builds the [`NetworkPolicy`] restricting the ingress of the metastore Pods of a [`HiveCluster`]
+use snafu::{OptionExt, ResultExt, Snafu};
+use stackable_hive_crd::{HiveCluster, HiveRole, APP_NAME, HIVE_PORT, METRICS_PORT};
+use stackable_operator::{
+    builder::meta::ObjectMetaBuilder,
+    client::Client,
+    commons::product_image_selection::ResolvedProductImage,
+    k8s_openapi::{
+        api::networking::v1::{
+            NetworkPolicy, NetworkPolicyIngressRule, NetworkPolicyPeer, NetworkPolicyPort,
+            NetworkPolicySpec,
+        },
+        apimachinery::pkg::{apis::meta::v1::LabelSelector, util::intstr::IntOrString},
+    },
+    kube::ResourceExt,
+    kvp::Labels,
+};
+
+use crate::controller::{build_recommended_labels, HIVE_CONTROLLER_NAME};
+
+#[derive(Snafu, Debug)]
+pub enum Error {
+    #[snafu(display("object defines no namespace"))]
+    ObjectHasNoNamespace,
+
+    #[snafu(display("object is missing metadata to build owner reference"))]
+    ObjectMissingMetadataForOwnerRef {
+        source: stackable_operator::builder::meta::Error,
+    },
+
+    #[snafu(display("failed to build Labels"))]
+    LabelBuild {
+        source: stackable_operator::kvp::LabelError,
+    },
+
+    #[snafu(display("failed to build Metadata"))]
+    MetadataBuild {
+        source: stackable_operator::builder::meta::Error,
+    },
+
+    #[snafu(display("failed to apply NetworkPolicy [{name}]"))]
+    ApplyNetworkPolicy {
+        source: stackable_operator::client::Error,
+        name: String,
+    },
+
+    #[snafu(display("failed to retrieve NetworkPolicy [{name}]"))]
+    GetNetworkPolicy {
+        source: stackable_operator::client::Error,
+        name: String,
+    },
+
+    #[snafu(display("failed to delete NetworkPolicy [{name}]"))]
+    DeleteNetworkPolicy {
+        source: stackable_operator::client::Error,
+        name: String,
+    },
+}
+
+/// The [`NetworkPolicy`] only allows the configured clients to connect to the Hive and metrics
+/// ports of all metastore Pods. All other ports, e.g. the JMX remote port, are blocked.
+pub fn build_network_policy(
+    hive: &HiveCluster,
+    resolved_product_image: &ResolvedProductImage,
+) -> Result<NetworkPolicy, Error> {
+    let role_name = HiveRole::MetaStore.to_string();
+    let config = &hive.spec.cluster_config.network_policy;
+
+    let pod_selector =
+        Labels::role_selector(hive, APP_NAME, &role_name).context(LabelBuildSnafu)?;
+    let client_selector = LabelSelector {
+        match_labels: (!config.client_labels.is_empty()).then(|| config.client_labels.clone()),
+        ..LabelSelector::default()
+    };
+    // Without a namespace selector, only Pods in the namespace of the NetworkPolicy are selected
+    let client_namespace_selector =
+        config
+            .client_namespace_labels
+            .as_ref()
+            .map(|client_namespace_labels| LabelSelector {
+                match_labels: (!client_namespace_labels.is_empty())
+                    .then(|| client_namespace_labels.clone()),
+                ..LabelSelector::default()
+            });
+
+    Ok(NetworkPolicy {
+        metadata: ObjectMetaBuilder::new()
+            .name_and_namespace(hive)
+            .ownerreference_from_resource(hive, None, Some(true))
+            .context(ObjectMissingMetadataForOwnerRefSnafu)?
+            .with_recommended_labels(build_recommended_labels(
+                hive,
+                &resolved_product_image.app_version_label,
+                &role_name,
+                "global",
+            ))
+            .context(MetadataBuildSnafu)?
+            .build(),
+        spec: Some(NetworkPolicySpec {
+            pod_selector: LabelSelector {
+                match_labels: Some(pod_selector.into()),
+                ..LabelSelector::default()
+            },
+            policy_types: Some(vec!["Ingress".to_string()]),
+            ingress: Some(vec![NetworkPolicyIngressRule {
+                from: Some(vec![NetworkPolicyPeer {
+                    pod_selector: Some(client_selector),
+                    namespace_selector: client_namespace_selector,
+                    ..NetworkPolicyPeer::default()
+                }]),
+                ports: Some(
+                    [HIVE_PORT, METRICS_PORT]
+                        .into_iter()
+                        .map(|port| NetworkPolicyPort {
+                            port: Some(IntOrString::Int(port.into())),
+                            protocol: Some("TCP".to_string()),
+                            ..NetworkPolicyPort::default()
+                        })
+                        .collect(),
+                ),
+            }]),
+            ..NetworkPolicySpec::default()
+        }),
+    })
+}
+
+/// Creates or updates the [`NetworkPolicy`]. It can not be managed by the `ClusterResources`, as
+/// `NetworkPolicy` is no `ClusterResource`.
+pub async fn apply_network_policy(
+    network_policy: &NetworkPolicy,
+    client: &Client,
+) -> Result<(), Error> {
+    client
+        .apply_patch(HIVE_CONTROLLER_NAME, network_policy, network_policy)
+        .await
+        .with_context(|_| ApplyNetworkPolicySnafu {
+            name: network_policy.name_any(),
+        })?;
+
+    Ok(())
+}
+
+/// Removes a previously created [`NetworkPolicy`] once it is disabled
+pub async fn delete_network_policy(hive: &HiveCluster, client: &Client) -> Result<(), Error> {
+    let name = hive.name_any();
+    let namespace = hive.namespace().context(ObjectHasNoNamespaceSnafu)?;
+
+    if let Some(network_policy) = client
+        .get_opt::<NetworkPolicy>(&name, &namespace)
+        .await
+        .with_context(|_| GetNetworkPolicySnafu { name: name.clone() })?
+    {
+        client
+            .delete(&network_policy)
+            .await
+            .with_context(|_| DeleteNetworkPolicySnafu { name })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::rstest;
+    use stackable_hive_crd::test_util::hive_from_yaml;
+    use std::collections::BTreeMap;
+
+    use crate::controller::DOCKER_IMAGE_BASE_NAME;
+
+    #[test]
+    fn test_network_policy() {
+        let hive = hive_from_yaml(
+            r#"
+            clusterConfig:
+              networkPolicy:
+                enabled: true
+                clientLabels:
+                  hive-client: "true"
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+
+        let network_policy = build_network_policy(&hive, &resolved_product_image).unwrap();
+        let spec = network_policy.spec.unwrap();
+
+        assert_eq!(network_policy.metadata.name.as_deref(), Some("simple-hive"));
+        assert_eq!(
+            spec.pod_selector.match_labels,
+            Some(BTreeMap::from([
+                (
+                    "app.kubernetes.io/component".to_string(),
+                    "metastore".to_string()
+                ),
+                (
+                    "app.kubernetes.io/instance".to_string(),
+                    "simple-hive".to_string()
+                ),
+                ("app.kubernetes.io/name".to_string(), "hive".to_string()),
+            ]))
+        );
+
+        let ingress = spec.ingress.unwrap();
+        assert_eq!(ingress.len(), 1);
+        assert_eq!(
+            ingress[0].from,
+            Some(vec![NetworkPolicyPeer {
+                pod_selector: Some(LabelSelector {
+                    match_labels: Some(BTreeMap::from([(
+                        "hive-client".to_string(),
+                        "true".to_string()
+                    )])),
+                    ..LabelSelector::default()
+                }),
+                ..NetworkPolicyPeer::default()
+            }])
+        );
+        assert_eq!(
+            ingress[0]
+                .ports
+                .iter()
+                .flatten()
+                .map(|port| port.port.clone())
+                .collect::<Vec<_>>(),
+            vec![Some(IntOrString::Int(9083)), Some(IntOrString::Int(9084))]
+        );
+    }
+
+    #[rstest]
+    #[case::same_namespace("{enabled: true}", None)]
+    #[case::all_namespaces(
+        "{enabled: true, clientNamespaceLabels: {}}",
+        Some(LabelSelector::default())
+    )]
+    #[case::labeled_namespaces(
+        "{enabled: true, clientNamespaceLabels: {kubernetes.io/metadata.name: trino}}",
+        Some(LabelSelector {
+            match_labels: Some(BTreeMap::from([(
+                "kubernetes.io/metadata.name".to_string(),
+                "trino".to_string()
+            )])),
+            ..LabelSelector::default()
+        })
+    )]
+    fn test_client_namespace_selector(
+        #[case] network_policy: &str,
+        #[case] expected: Option<LabelSelector>,
+    ) {
+        let hive = hive_from_yaml(&format!(
+            "clusterConfig: {{networkPolicy: {network_policy}}}"
+        ));
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+
+        let network_policy = build_network_policy(&hive, &resolved_product_image).unwrap();
+        let ingress = network_policy.spec.unwrap().ingress.unwrap();
+
+        assert_eq!(
+            ingress[0]
+                .from
+                .iter()
+                .flatten()
+                .map(|peer| peer.namespace_selector.clone())
+                .collect::<Vec<_>>(),
+            vec![expected]
+        );
+    }
+}
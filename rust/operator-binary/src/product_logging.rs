@@ -1,5 +1,3 @@
-use crate::controller::MAX_HIVE_LOG_FILES_SIZE;
-
 use snafu::{OptionExt, ResultExt, Snafu};
 use stackable_hive_crd::{
     Container, HiveCluster, HIVE_METASTORE_LOG4J2_PROPERTIES, STACKABLE_LOG_DIR,
@@ -9,7 +7,7 @@ use stackable_operator::{
     client::Client,
     k8s_openapi::api::core::v1::ConfigMap,
     kube::ResourceExt,
-    memory::BinaryMultiple,
+    memory::{BinaryMultiple, MemoryQuantity},
     product_logging::{
         self,
         spec::{ContainerLogConfig, ContainerLogConfigChoice, Logging},
@@ -82,6 +80,7 @@ pub fn extend_role_group_config_map(
     rolegroup: &RoleGroupRef<HiveCluster>,
     vector_aggregator_address: Option<&str>,
     logging: &Logging<Container>,
+    max_log_files_size: MemoryQuantity,
     cm_builder: &mut ConfigMapBuilder,
 ) -> Result<()> {
     if let Some(ContainerLogConfig {
@@ -96,7 +95,7 @@ pub fn extend_role_group_config_map(
                     container = Container::Hive
                 ),
                 HIVE_LOG_FILE,
-                MAX_HIVE_LOG_FILES_SIZE
+                max_log_files_size
                     .scale_to(BinaryMultiple::Mebi)
                     .floor()
                     .value as u32,
@@ -128,3 +127,68 @@ pub fn extend_role_group_config_map(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use stackable_hive_crd::{test_util::hive_from_yaml, HiveRole};
+    use stackable_operator::builder::meta::ObjectMetaBuilder;
+
+    use crate::controller::MAX_HIVE_LOG_FILES_SIZE;
+
+    #[test]
+    fn test_custom_logger_levels() {
+        let hive = hive_from_yaml(
+            r#"
+            metastore:
+              config:
+                logging:
+                  containers:
+                    hive:
+                      loggers:
+                        DataNucleus:
+                          level: DEBUG
+              roleGroups:
+                default:
+                  replicas: 1
+                  config:
+                    logging:
+                      containers:
+                        hive:
+                          loggers:
+                            org.apache.hadoop.hive.metastore:
+                              level: TRACE
+            "#,
+        );
+        let rolegroup_ref = hive.metastore_rolegroup_ref("default");
+        let merged_config = hive
+            .merged_config(&HiveRole::MetaStore, &rolegroup_ref)
+            .unwrap();
+
+        let mut cm_builder = ConfigMapBuilder::new();
+        cm_builder.metadata(ObjectMetaBuilder::new().name("simple-hive").build());
+        extend_role_group_config_map(
+            &rolegroup_ref,
+            None,
+            &merged_config.logging,
+            MAX_HIVE_LOG_FILES_SIZE,
+            &mut cm_builder,
+        )
+        .unwrap();
+        let log4j2_properties = cm_builder
+            .build()
+            .unwrap()
+            .data
+            .unwrap()
+            .remove(HIVE_METASTORE_LOG4J2_PROPERTIES)
+            .expect("the log4j2 properties must be generated");
+
+        assert!(log4j2_properties.contains("logger.DataNucleus.name = DataNucleus"));
+        assert!(log4j2_properties.contains("logger.DataNucleus.level = DEBUG"));
+        assert!(log4j2_properties.contains(
+            "logger.org_apache_hadoop_hive_metastore.name = org.apache.hadoop.hive.metastore"
+        ));
+        assert!(log4j2_properties.contains("logger.org_apache_hadoop_hive_metastore.level = TRACE"));
+    }
+}
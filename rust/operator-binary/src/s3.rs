@@ -0,0 +1,450 @@
+//! Builds the S3A properties of the `hive-site.xml`
+use std::collections::BTreeMap;
+
+use snafu::{ResultExt, Snafu};
+use stackable_hive_crd::{
+    HiveCluster, MetaStoreConfig, S3_ENDPOINT_ENV, S3_PROXY_PASSWORD_ENV, S3_PROXY_USERNAME_ENV,
+    S3_REGION_ENV,
+};
+use stackable_operator::commons::s3::{S3AccessStyle, S3ConnectionSpec, S3Error};
+
+#[derive(Snafu, Debug)]
+pub enum Error {
+    #[snafu(display("failed to determine the S3 endpoint"))]
+    S3Endpoint { source: S3Error },
+}
+
+/// The S3A properties for the given S3 connection and the S3 tuning settings, followed by the
+/// per-bucket overrides
+pub fn s3_config_properties(
+    hive: &HiveCluster,
+    s3_connection_spec: Option<&S3ConnectionSpec>,
+) -> Result<BTreeMap<String, String>, Error> {
+    let mut properties = BTreeMap::new();
+
+    if let Some(s3) = s3_connection_spec {
+        properties.insert(
+            MetaStoreConfig::S3_ENDPOINT.to_string(),
+            s3.endpoint().context(S3EndpointSnafu)?.to_string(),
+        );
+
+        if let Some((access_key_file, secret_key_file)) = s3.credentials_mount_paths() {
+            // Will be replaced by config-utils
+            properties.insert(
+                MetaStoreConfig::S3_ACCESS_KEY.to_string(),
+                format!("${{file:UTF-8:{access_key_file}}}"),
+            );
+            properties.insert(
+                MetaStoreConfig::S3_SECRET_KEY.to_string(),
+                format!("${{file:UTF-8:{secret_key_file}}}"),
+            );
+        }
+
+        properties.insert(
+            MetaStoreConfig::S3_SSL_ENABLED.to_string(),
+            s3.tls.uses_tls().to_string(),
+        );
+        properties.insert(
+            MetaStoreConfig::S3_PATH_STYLE_ACCESS.to_string(),
+            (s3.access_style == S3AccessStyle::Path).to_string(),
+        );
+    }
+
+    let s3_tuning = &hive.spec.cluster_config.s3_tuning;
+    if let Some(region) = &s3_tuning.region {
+        if s3_connection_spec.is_none() {
+            properties.insert(
+                MetaStoreConfig::S3_ENDPOINT.to_string(),
+                format!("s3.{region}.amazonaws.com"),
+            );
+        }
+        properties.insert(
+            MetaStoreConfig::S3_ENDPOINT_REGION.to_string(),
+            region.to_string(),
+        );
+    }
+    if s3_tuning.endpoint_secret.is_some() {
+        // Will be replaced by config-utils with the env vars from the Secret
+        properties.insert(
+            MetaStoreConfig::S3_ENDPOINT.to_string(),
+            format!("${{env:{S3_ENDPOINT_ENV}}}"),
+        );
+        properties.insert(
+            MetaStoreConfig::S3_ENDPOINT_REGION.to_string(),
+            format!("${{env:{S3_REGION_ENV}}}"),
+        );
+    }
+    if let Some(path_style_access) = s3_tuning.path_style_access {
+        // Takes precedence over the value derived from the access style
+        properties.insert(
+            MetaStoreConfig::S3_PATH_STYLE_ACCESS.to_string(),
+            path_style_access.to_string(),
+        );
+    }
+    if let Some(credentials_provider) = &s3_tuning.credentials_provider {
+        properties.insert(
+            MetaStoreConfig::S3_AWS_CREDENTIALS_PROVIDER.to_string(),
+            credentials_provider.to_string(),
+        );
+    }
+    if let Some(canned_acl) = &s3_tuning.canned_acl {
+        properties.insert(
+            MetaStoreConfig::S3_CANNED_ACL.to_string(),
+            canned_acl.to_string(),
+        );
+    }
+    if let Some(algorithm) = s3_tuning.server_side_encryption_algorithm {
+        properties.insert(
+            MetaStoreConfig::S3_SERVER_SIDE_ENCRYPTION_ALGORITHM.to_string(),
+            algorithm.to_string(),
+        );
+    }
+    if let Some(key) = &s3_tuning.server_side_encryption_key {
+        properties.insert(
+            MetaStoreConfig::S3_SERVER_SIDE_ENCRYPTION_KEY.to_string(),
+            key.to_string(),
+        );
+    }
+    if let Some(attempts_maximum) = s3_tuning.attempts_maximum {
+        properties.insert(
+            MetaStoreConfig::S3_ATTEMPTS_MAXIMUM.to_string(),
+            attempts_maximum.to_string(),
+        );
+    }
+    if let Some(retry_limit) = s3_tuning.retry_limit {
+        properties.insert(
+            MetaStoreConfig::S3_RETRY_LIMIT.to_string(),
+            retry_limit.to_string(),
+        );
+    }
+    if let Some(change_detection_mode) = s3_tuning.change_detection_mode {
+        properties.insert(
+            MetaStoreConfig::S3_CHANGE_DETECTION_MODE.to_string(),
+            change_detection_mode.to_string(),
+        );
+    }
+    if let Some(change_detection_source) = s3_tuning.change_detection_source {
+        properties.insert(
+            MetaStoreConfig::S3_CHANGE_DETECTION_SOURCE.to_string(),
+            change_detection_source.to_string(),
+        );
+    }
+
+    if let Some(proxy) = &s3_tuning.proxy {
+        properties.insert(
+            MetaStoreConfig::S3_PROXY_HOST.to_string(),
+            proxy.host.to_string(),
+        );
+        if let Some(port) = proxy.port {
+            properties.insert(MetaStoreConfig::S3_PROXY_PORT.to_string(), port.to_string());
+        }
+        if proxy.credentials_secret.is_some() {
+            // Will be replaced by config-utils with the env vars from the Secret
+            properties.insert(
+                MetaStoreConfig::S3_PROXY_USERNAME.to_string(),
+                format!("${{env:{S3_PROXY_USERNAME_ENV}}}"),
+            );
+            properties.insert(
+                MetaStoreConfig::S3_PROXY_PASSWORD.to_string(),
+                format!("${{env:{S3_PROXY_PASSWORD_ENV}}}"),
+            );
+        }
+    }
+
+    for (bucket, overrides) in &s3_tuning.bucket_overrides {
+        for (key, value) in overrides {
+            properties.insert(
+                format!(
+                    "{prefix}{bucket}.{key}",
+                    prefix = MetaStoreConfig::S3_BUCKET_PREFIX
+                ),
+                value.to_string(),
+            );
+        }
+    }
+
+    Ok(properties)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use stackable_hive_crd::test_util::hive_from_yaml;
+
+    /// Parses a [`HiveCluster`] with the given `s3Tuning` settings
+    fn hive_with_s3_tuning(s3_tuning: &str) -> HiveCluster {
+        let mut hive = hive_from_yaml("metastore: {roleGroups: {default: {replicas: 1}}}");
+        hive.spec.cluster_config.s3_tuning =
+            serde_yaml::from_str(s3_tuning).expect("illegal test s3Tuning");
+        hive
+    }
+
+    #[test]
+    fn test_bucket_overrides() {
+        let hive = hive_with_s3_tuning(
+            r#"
+            bucketOverrides:
+              archive:
+                endpoint: https://archive.example.com
+                path.style.access: "true"
+              logs:
+                endpoint: https://logs.example.com
+            "#,
+        );
+
+        assert_eq!(
+            s3_config_properties(&hive, None).unwrap(),
+            BTreeMap::from([
+                (
+                    "fs.s3a.bucket.archive.endpoint".to_string(),
+                    "https://archive.example.com".to_string()
+                ),
+                (
+                    "fs.s3a.bucket.archive.path.style.access".to_string(),
+                    "true".to_string()
+                ),
+                (
+                    "fs.s3a.bucket.logs.endpoint".to_string(),
+                    "https://logs.example.com".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_retry_settings() {
+        let hive = hive_with_s3_tuning(
+            r#"
+            attemptsMaximum: 20
+            retryLimit: 10
+            "#,
+        );
+
+        assert_eq!(
+            s3_config_properties(&hive, None).unwrap(),
+            BTreeMap::from([
+                ("fs.s3a.attempts.maximum".to_string(), "20".to_string()),
+                ("fs.s3a.retry.limit".to_string(), "10".to_string()),
+            ])
+        );
+
+        let hive = hive_with_s3_tuning("{}");
+
+        assert_eq!(s3_config_properties(&hive, None).unwrap(), BTreeMap::new());
+    }
+
+    #[test]
+    fn test_change_detection() {
+        let hive = hive_with_s3_tuning(
+            r#"
+            changeDetectionMode: warn
+            changeDetectionSource: versionid
+            "#,
+        );
+
+        assert_eq!(
+            s3_config_properties(&hive, None).unwrap(),
+            BTreeMap::from([
+                (
+                    "fs.s3a.change.detection.mode".to_string(),
+                    "warn".to_string()
+                ),
+                (
+                    "fs.s3a.change.detection.source".to_string(),
+                    "versionid".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_path_style_access_override() {
+        let hive = hive_with_s3_tuning("pathStyleAccess: true");
+        let s3_connection_spec: S3ConnectionSpec = serde_yaml::from_str(
+            r#"
+            host: minio
+            port: 9000
+            accessStyle: VirtualHosted
+            "#,
+        )
+        .expect("illegal test S3 connection");
+
+        assert_eq!(
+            s3_config_properties(&hive, Some(&s3_connection_spec))
+                .unwrap()
+                .get(MetaStoreConfig::S3_PATH_STYLE_ACCESS),
+            Some(&"true".to_string())
+        );
+
+        let hive = hive_with_s3_tuning("{}");
+
+        assert_eq!(
+            s3_config_properties(&hive, Some(&s3_connection_spec))
+                .unwrap()
+                .get(MetaStoreConfig::S3_PATH_STYLE_ACCESS),
+            Some(&"false".to_string())
+        );
+    }
+
+    #[test]
+    fn test_credentials_provider() {
+        let hive = hive_with_s3_tuning(
+            r#"
+            credentialsProvider: com.amazonaws.auth.WebIdentityTokenCredentialsProvider,com.amazonaws.auth.EnvironmentVariableCredentialsProvider
+            "#,
+        );
+
+        assert_eq!(
+            s3_config_properties(&hive, None).unwrap(),
+            BTreeMap::from([(
+                "fs.s3a.aws.credentials.provider".to_string(),
+                "com.amazonaws.auth.WebIdentityTokenCredentialsProvider,com.amazonaws.auth.EnvironmentVariableCredentialsProvider".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn test_canned_acl() {
+        let hive = hive_with_s3_tuning("cannedAcl: BucketOwnerFullControl");
+
+        assert_eq!(
+            s3_config_properties(&hive, None).unwrap(),
+            BTreeMap::from([(
+                "fs.s3a.canned.acl".to_string(),
+                "BucketOwnerFullControl".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn test_server_side_encryption() {
+        let hive = hive_with_s3_tuning(
+            r#"
+            serverSideEncryptionAlgorithm: SSE-KMS
+            serverSideEncryptionKey: arn:aws:kms:eu-central-1:123456789012:key/hive
+            "#,
+        );
+
+        assert_eq!(
+            s3_config_properties(&hive, None).unwrap(),
+            BTreeMap::from([
+                (
+                    "fs.s3a.server-side-encryption-algorithm".to_string(),
+                    "SSE-KMS".to_string()
+                ),
+                (
+                    "fs.s3a.server-side-encryption.key".to_string(),
+                    "arn:aws:kms:eu-central-1:123456789012:key/hive".to_string()
+                ),
+            ])
+        );
+
+        let hive = hive_with_s3_tuning("serverSideEncryptionAlgorithm: AES256");
+
+        assert_eq!(
+            s3_config_properties(&hive, None).unwrap(),
+            BTreeMap::from([(
+                "fs.s3a.server-side-encryption-algorithm".to_string(),
+                "AES256".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn test_proxy() {
+        let hive = hive_with_s3_tuning(
+            r#"
+            proxy:
+              host: proxy.example.com
+              port: 3128
+              credentialsSecret: s3-proxy-credentials
+            "#,
+        );
+
+        assert_eq!(
+            s3_config_properties(&hive, None).unwrap(),
+            BTreeMap::from([
+                (
+                    "fs.s3a.proxy.host".to_string(),
+                    "proxy.example.com".to_string()
+                ),
+                ("fs.s3a.proxy.port".to_string(), "3128".to_string()),
+                (
+                    "fs.s3a.proxy.username".to_string(),
+                    "${env:S3_PROXY_USERNAME}".to_string()
+                ),
+                (
+                    "fs.s3a.proxy.password".to_string(),
+                    "${env:S3_PROXY_PASSWORD}".to_string()
+                ),
+            ])
+        );
+
+        let hive = hive_with_s3_tuning(
+            r#"
+            proxy:
+              host: proxy.example.com
+            "#,
+        );
+
+        assert_eq!(
+            s3_config_properties(&hive, None).unwrap(),
+            BTreeMap::from([(
+                "fs.s3a.proxy.host".to_string(),
+                "proxy.example.com".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn test_endpoint_secret() {
+        let hive = hive_with_s3_tuning(
+            r#"
+            region: eu-central-1
+            endpointSecret: s3-endpoint
+            "#,
+        );
+        let s3_connection_spec: S3ConnectionSpec = serde_yaml::from_str(
+            r#"
+            host: minio
+            port: 9000
+            "#,
+        )
+        .expect("illegal test S3 connection");
+
+        let properties = s3_config_properties(&hive, Some(&s3_connection_spec)).unwrap();
+
+        assert_eq!(
+            properties.get(MetaStoreConfig::S3_ENDPOINT),
+            Some(&"${env:S3_ENDPOINT}".to_string())
+        );
+        assert_eq!(
+            properties.get(MetaStoreConfig::S3_ENDPOINT_REGION),
+            Some(&"${env:S3_REGION}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_endpoint_derived_from_region() {
+        let hive = hive_with_s3_tuning(
+            r#"
+            region: eu-central-1
+            "#,
+        );
+
+        assert_eq!(
+            s3_config_properties(&hive, None).unwrap(),
+            BTreeMap::from([
+                (
+                    "fs.s3a.endpoint".to_string(),
+                    "s3.eu-central-1.amazonaws.com".to_string()
+                ),
+                (
+                    "fs.s3a.endpoint.region".to_string(),
+                    "eu-central-1".to_string()
+                ),
+            ])
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Builds the Prometheus Operator [`ServiceMonitor`] scraping the metrics of a [`HiveCluster`]
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Snafu};
+use stackable_hive_crd::{HiveCluster, HiveRole, APP_NAME, METRICS_PORT_NAME};
+use stackable_operator::{
+    builder::meta::ObjectMetaBuilder,
+    client::Client,
+    cluster_resources::ClusterResource,
+    commons::product_image_selection::ResolvedProductImage,
+    k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector,
+    kube::{CustomResource, ResourceExt},
+    kvp::{Label, Labels},
+    schemars::{self, JsonSchema},
+};
+
+use crate::controller::build_recommended_labels;
+
+#[derive(Snafu, Debug)]
+pub enum Error {
+    #[snafu(display("object defines no namespace"))]
+    ObjectHasNoNamespace,
+
+    #[snafu(display("object is missing metadata to build owner reference"))]
+    ObjectMissingMetadataForOwnerRef {
+        source: stackable_operator::builder::meta::Error,
+    },
+
+    #[snafu(display("failed to build Labels"))]
+    LabelBuild {
+        source: stackable_operator::kvp::LabelError,
+    },
+
+    #[snafu(display("failed to build Metadata"))]
+    MetadataBuild {
+        source: stackable_operator::builder::meta::Error,
+    },
+
+    #[snafu(display("failed to retrieve ServiceMonitor [{name}]"))]
+    GetServiceMonitor {
+        source: stackable_operator::client::Error,
+        name: String,
+    },
+
+    #[snafu(display("failed to delete ServiceMonitor [{name}]"))]
+    DeleteServiceMonitor {
+        source: stackable_operator::client::Error,
+        name: String,
+    },
+}
+
+/// The subset of the `monitoring.coreos.com/v1` `ServiceMonitor` used by the operator.
+#[derive(Clone, CustomResource, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[kube(
+    group = "monitoring.coreos.com",
+    version = "v1",
+    kind = "ServiceMonitor",
+    plural = "servicemonitors",
+    namespaced,
+    crates(
+        kube_core = "stackable_operator::kube::core",
+        k8s_openapi = "stackable_operator::k8s_openapi",
+        schemars = "stackable_operator::schemars"
+    )
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceMonitorSpec {
+    pub selector: LabelSelector,
+    pub endpoints: Vec<ServiceMonitorEndpoint>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceMonitorEndpoint {
+    pub port: String,
+}
+
+impl ClusterResource for ServiceMonitor {}
+
+/// The [`ServiceMonitor`] selects the metrics port of all metastore rolegroup services
+pub fn build_service_monitor(
+    hive: &HiveCluster,
+    resolved_product_image: &ResolvedProductImage,
+) -> Result<ServiceMonitor, Error> {
+    let role_name = HiveRole::MetaStore.to_string();
+    let config = &hive.spec.cluster_config.monitoring.service_monitor;
+
+    let mut selector =
+        Labels::role_selector(hive, APP_NAME, &role_name).context(LabelBuildSnafu)?;
+    selector.insert(Label::try_from(("prometheus.io/scrape", "true")).context(LabelBuildSnafu)?);
+
+    Ok(ServiceMonitor {
+        metadata: ObjectMetaBuilder::new()
+            .name_and_namespace(hive)
+            .ownerreference_from_resource(hive, None, Some(true))
+            .context(ObjectMissingMetadataForOwnerRefSnafu)?
+            .with_recommended_labels(build_recommended_labels(
+                hive,
+                &resolved_product_image.app_version_label,
+                &role_name,
+                "global",
+            ))
+            .context(MetadataBuildSnafu)?
+            .with_labels(Labels::try_from(config.labels.clone()).context(LabelBuildSnafu)?)
+            .build(),
+        spec: ServiceMonitorSpec {
+            selector: LabelSelector {
+                match_labels: Some(selector.into()),
+                ..LabelSelector::default()
+            },
+            endpoints: vec![ServiceMonitorEndpoint {
+                port: METRICS_PORT_NAME.to_string(),
+            }],
+        },
+    })
+}
+
+/// Removes a previously created [`ServiceMonitor`] once it is disabled
+pub async fn delete_service_monitor(hive: &HiveCluster, client: &Client) -> Result<(), Error> {
+    let name = hive.name_any();
+    let namespace = hive.namespace().context(ObjectHasNoNamespaceSnafu)?;
+
+    if let Some(service_monitor) = client
+        .get_opt::<ServiceMonitor>(&name, &namespace)
+        .await
+        .with_context(|_| GetServiceMonitorSnafu { name: name.clone() })?
+    {
+        client
+            .delete(&service_monitor)
+            .await
+            .with_context(|_| DeleteServiceMonitorSnafu { name })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use stackable_hive_crd::test_util::hive_from_yaml;
+    use std::collections::BTreeMap;
+
+    use crate::controller::DOCKER_IMAGE_BASE_NAME;
+
+    #[test]
+    fn test_service_monitor() {
+        let hive = hive_from_yaml(
+            r#"
+            clusterConfig:
+              monitoring:
+                serviceMonitor:
+                  enabled: true
+                  labels:
+                    release: prometheus
+            metastore:
+              roleGroups:
+                default:
+                  replicas: 1
+            "#,
+        );
+        let resolved_product_image = hive
+            .spec
+            .image
+            .resolve(DOCKER_IMAGE_BASE_NAME, crate::built_info::PKG_VERSION);
+
+        let service_monitor = build_service_monitor(&hive, &resolved_product_image).unwrap();
+
+        assert_eq!(service_monitor.name_any(), "simple-hive");
+        assert_eq!(service_monitor.namespace().as_deref(), Some("default"));
+        assert_eq!(
+            service_monitor.labels().get("release").map(String::as_str),
+            Some("prometheus")
+        );
+        assert_eq!(
+            service_monitor.spec.selector.match_labels,
+            Some(BTreeMap::from([
+                (
+                    "app.kubernetes.io/component".to_string(),
+                    "metastore".to_string()
+                ),
+                (
+                    "app.kubernetes.io/instance".to_string(),
+                    "simple-hive".to_string()
+                ),
+                ("app.kubernetes.io/name".to_string(), "hive".to_string()),
+                ("prometheus.io/scrape".to_string(), "true".to_string()),
+            ]))
+        );
+        assert_eq!(
+            service_monitor.spec.endpoints,
+            vec![ServiceMonitorEndpoint {
+                port: "metrics".to_string(),
+            }]
+        );
+    }
+}
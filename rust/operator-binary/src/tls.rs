@@ -0,0 +1,103 @@
+//! Mutual TLS between the metastore and its clients
+use snafu::{ResultExt, Snafu};
+use stackable_hive_crd::HiveCluster;
+use stackable_operator::{
+    builder::{
+        self,
+        pod::{
+            container::ContainerBuilder,
+            volume::{
+                SecretFormat, SecretOperatorVolumeSourceBuilder,
+                SecretOperatorVolumeSourceBuilderError, VolumeBuilder,
+            },
+            PodBuilder,
+        },
+    },
+    kube::ResourceExt,
+};
+use std::collections::BTreeMap;
+
+pub const TLS_VOLUME_NAME: &str = "tls";
+pub const TLS_DIR: &str = "/stackable/tls";
+const TLS_STORE_PASSWORD: &str = "changeit";
+
+#[derive(Snafu, Debug)]
+#[allow(clippy::enum_variant_names)] // all variants have the same prefix: `Add`
+pub enum Error {
+    #[snafu(display("failed to add TLS secret volume"))]
+    AddTlsSecretVolume {
+        source: SecretOperatorVolumeSourceBuilderError,
+    },
+
+    #[snafu(display("failed to add needed volume"))]
+    AddVolume { source: builder::pod::Error },
+
+    #[snafu(display("failed to add needed volumeMount"))]
+    AddVolumeMount {
+        source: builder::pod::container::Error,
+    },
+}
+
+/// Mounts the keystore with the server certificate and the truststore with the CA of the TLS
+/// SecretClass, which is used to verify the client certificates.
+pub fn add_tls_pod_config(
+    hive: &HiveCluster,
+    cb: &mut ContainerBuilder,
+    pb: &mut PodBuilder,
+) -> Result<(), Error> {
+    if let Some(tls_secret_class) = hive.tls_authentication_secret_class() {
+        let tls_secret_operator_volume = SecretOperatorVolumeSourceBuilder::new(tls_secret_class)
+            .with_pod_scope()
+            .with_service_scope(hive.name_any())
+            .with_format(SecretFormat::TlsPkcs12)
+            .with_tls_pkcs12_password(TLS_STORE_PASSWORD)
+            .build()
+            .context(AddTlsSecretVolumeSnafu)?;
+        pb.add_volume(
+            VolumeBuilder::new(TLS_VOLUME_NAME)
+                .ephemeral(tls_secret_operator_volume)
+                .build(),
+        )
+        .context(AddVolumeSnafu)?;
+        cb.add_volume_mount(TLS_VOLUME_NAME, TLS_DIR)
+            .context(AddVolumeMountSnafu)?;
+    }
+
+    Ok(())
+}
+
+/// The `hive-site.xml` properties enabling TLS on the Thrift port with the mounted key- and
+/// truststore.
+pub fn tls_config_properties(hive: &HiveCluster) -> BTreeMap<String, String> {
+    if !hive.has_tls_authentication_enabled() {
+        return BTreeMap::new();
+    }
+
+    BTreeMap::from([
+        ("hive.metastore.use.SSL".to_string(), "true".to_string()),
+        (
+            "hive.metastore.keystore.path".to_string(),
+            format!("{TLS_DIR}/keystore.p12"),
+        ),
+        (
+            "hive.metastore.keystore.password".to_string(),
+            TLS_STORE_PASSWORD.to_string(),
+        ),
+        (
+            "hive.metastore.keystore.type".to_string(),
+            "PKCS12".to_string(),
+        ),
+        (
+            "hive.metastore.truststore.path".to_string(),
+            format!("{TLS_DIR}/truststore.p12"),
+        ),
+        (
+            "hive.metastore.truststore.password".to_string(),
+            TLS_STORE_PASSWORD.to_string(),
+        ),
+        (
+            "hive.metastore.truststore.type".to_string(),
+            "PKCS12".to_string(),
+        ),
+    ])
+}
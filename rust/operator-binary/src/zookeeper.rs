@@ -0,0 +1,75 @@
+//! ZooKeeper based service discovery of the metastore instances
+use stackable_hive_crd::{HiveCluster, MetaStoreConfig};
+use std::collections::BTreeMap;
+
+/// The `hive-site.xml` properties registering the metastore in ZooKeeper. In the ZooKeeper
+/// discovery mode, Hive reads the ZooKeeper quorum from the metastore URIs.
+pub fn zookeeper_discovery_config_properties(hive: &HiveCluster) -> BTreeMap<String, String> {
+    let Some(zookeeper_discovery) = &hive.spec.cluster_config.zookeeper_discovery else {
+        return BTreeMap::new();
+    };
+
+    let mut properties = BTreeMap::from([
+        (
+            MetaStoreConfig::METASTORE_SERVICE_DISCOVERY_MODE.to_string(),
+            "ZOOKEEPER".to_string(),
+        ),
+        (
+            MetaStoreConfig::METASTORE_URIS.to_string(),
+            zookeeper_discovery.quorum.clone(),
+        ),
+    ]);
+
+    if let Some(namespace) = &zookeeper_discovery.namespace {
+        properties.insert(
+            MetaStoreConfig::METASTORE_ZOOKEEPER_NAMESPACE.to_string(),
+            namespace.clone(),
+        );
+    }
+
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::rstest;
+    use stackable_hive_crd::test_util::hive_from_yaml;
+
+    #[rstest]
+    #[case::namespace(
+        Some("{quorum: 'zk-0.zk:2181,zk-1.zk:2181', namespace: hive-metastore}"),
+        &[
+            ("hive.metastore.service.discovery.mode", "ZOOKEEPER"),
+            ("hive.metastore.uris", "zk-0.zk:2181,zk-1.zk:2181"),
+            ("hive.metastore.zookeeper.namespace", "hive-metastore"),
+        ]
+    )]
+    // Hive registers the metastore below its default node `hivemetastore`
+    #[case::default_namespace(
+        Some("{quorum: 'zk:2181'}"),
+        &[
+            ("hive.metastore.service.discovery.mode", "ZOOKEEPER"),
+            ("hive.metastore.uris", "zk:2181"),
+        ]
+    )]
+    #[case::no_zookeeper_discovery(None, &[])]
+    fn test_zookeeper_discovery(
+        #[case] zookeeper_discovery: Option<&str>,
+        #[case] expected: &[(&str, &str)],
+    ) {
+        let mut hive = hive_from_yaml("metastore: {roleGroups: {default: {replicas: 1}}}");
+        hive.spec.cluster_config.zookeeper_discovery = zookeeper_discovery.map(|discovery| {
+            serde_yaml::from_str(discovery).expect("illegal test zookeeperDiscovery")
+        });
+
+        assert_eq!(
+            zookeeper_discovery_config_properties(&hive),
+            expected
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        );
+    }
+}